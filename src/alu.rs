@@ -0,0 +1,672 @@
+use crate::error::{next_word, offset, parse_lines, parse_word};
+use crate::ParseError;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// One of the ALU's four registers.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Register {
+    W,
+    X,
+    Y,
+    Z,
+}
+
+impl Register {
+    pub const ALL: [Self; 4] = [Self::W, Self::X, Self::Y, Self::Z];
+
+    fn index(self) -> usize {
+        match self {
+            Self::W => 0,
+            Self::X => 1,
+            Self::Y => 2,
+            Self::Z => 3,
+        }
+    }
+}
+
+impl FromStr for Register {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let register = match s {
+            "w" => Self::W,
+            "x" => Self::X,
+            "y" => Self::Y,
+            "z" => Self::Z,
+            _ => return Err(ParseError::new(format!("unknown register `{}`", s))),
+        };
+        Ok(register)
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::W => "w",
+            Self::X => "x",
+            Self::Y => "y",
+            Self::Z => "z",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The second argument of an instruction: either a register or a number.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Operand {
+    Register(Register),
+    Number(i64),
+}
+
+impl FromStr for Operand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(register) = s.parse() {
+            return Ok(Self::Register(register));
+        }
+        s.parse()
+            .map(Self::Number)
+            .map_err(|_| ParseError::new(format!("unknown operand `{}`", s)))
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Register(register) => write!(f, "{}", register),
+            Self::Number(number) => write!(f, "{}", number),
+        }
+    }
+}
+
+/// The arithmetic an instruction does, storing `a op b` into its first argument `a`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Add,
+    Mul,
+    /// Division, rounding towards zero.
+    Div,
+    /// The remainder after division, only defined for `a >= 0` and `b > 0`.
+    Mod,
+    /// One if the arguments are equal, zero otherwise.
+    Eql,
+}
+
+impl Operation {
+    /// `a op b`, or the fault that stops the ALU.
+    pub fn apply(self, a: i64, b: i64) -> Result<i64, FaultKind> {
+        match self {
+            Self::Add => a.checked_add(b).ok_or(FaultKind::Overflow),
+            Self::Mul => a.checked_mul(b).ok_or(FaultKind::Overflow),
+            Self::Div if b == 0 => Err(FaultKind::DivideByZero),
+            Self::Div => a.checked_div(b).ok_or(FaultKind::Overflow),
+            Self::Mod if b == 0 => Err(FaultKind::DivideByZero),
+            Self::Mod if a < 0 || b < 0 => Err(FaultKind::NegativeModulo),
+            Self::Mod => Ok(a % b),
+            Self::Eql => Ok(i64::from(a == b)),
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let operation = match s {
+            "add" => Self::Add,
+            "mul" => Self::Mul,
+            "div" => Self::Div,
+            "mod" => Self::Mod,
+            "eql" => Self::Eql,
+            _ => return Err(ParseError::new(format!("unknown operation `{}`", s))),
+        };
+        Ok(operation)
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Add => "add",
+            Self::Mul => "mul",
+            Self::Div => "div",
+            Self::Mod => "mod",
+            Self::Eql => "eql",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A single ALU instruction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Instruction {
+    /// Read the next input value into a register.
+    Inp(Register),
+    Binary(Operation, Register, Operand),
+}
+
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let instruction = match words.next() {
+            Some("inp") => Self::Inp(next_word(s, &mut words, "a register")?),
+            Some(word) => {
+                let operation = parse_word(s, word, "an instruction")?;
+                let register = next_word(s, &mut words, "a register")?;
+                let operand = next_word(s, &mut words, "a register or a number")?;
+                Self::Binary(operation, register, operand)
+            }
+            None => {
+                return Err(ParseError::new(
+                    "expected an instruction, found a blank line",
+                ))
+            }
+        };
+        if let Some(word) = words.next() {
+            let message = format!("expected end of line, found `{}`", word);
+            return Err(ParseError::new(message).column_offset(offset(s, word)));
+        }
+        Ok(instruction)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Inp(register) => write!(f, "inp {}", register),
+            Self::Binary(operation, register, operand) => {
+                write!(f, "{} {} {}", operation, register, operand)
+            }
+        }
+    }
+}
+
+/// What went wrong when the ALU stopped.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FaultKind {
+    DivideByZero,
+    /// `mod` with a negative argument.
+    NegativeModulo,
+    /// A result that does not fit in 64 bits.
+    Overflow,
+    /// `inp` with no input left to read.
+    EndOfInput,
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            Self::DivideByZero => "division by zero",
+            Self::NegativeModulo => "modulo of a negative number",
+            Self::Overflow => "arithmetic overflow",
+            Self::EndOfInput => "ran out of input",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// The ALU stopped part way through a program.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Fault {
+    /// The index of the failing instruction, counting from zero.
+    pub instruction: usize,
+    pub kind: FaultKind,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "instruction {}: {}", self.instruction + 1, self.kind)
+    }
+}
+
+impl std::error::Error for Fault {}
+
+/// The contents of the ALU's registers.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Registers([i64; 4]);
+
+impl Registers {
+    pub fn get(&self, register: Register) -> i64 {
+        self.0[register.index()]
+    }
+
+    pub fn set(&mut self, register: Register, value: i64) {
+        self.0[register.index()] = value;
+    }
+
+    fn operand(&self, operand: Operand) -> i64 {
+        match operand {
+            Operand::Register(register) => self.get(register),
+            Operand::Number(number) => number,
+        }
+    }
+
+    // Carry out an instruction other than `inp`.
+    fn apply(
+        &mut self,
+        operation: Operation,
+        register: Register,
+        operand: Operand,
+    ) -> Result<(), FaultKind> {
+        let value = operation.apply(self.get(register), self.operand(operand))?;
+        self.set(register, value);
+        Ok(())
+    }
+}
+
+/// The values something might take, from `min` to `max` inclusive.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Range {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Range {
+    /// The values from `min` to `max`, if there are any.
+    pub fn new(min: i64, max: i64) -> Option<Self> {
+        (min <= max).then_some(Self { min, max })
+    }
+
+    pub fn single(value: i64) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    pub fn contains(self, value: i64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    // The smallest range holding every product of corners of these ranges, under `combine`.
+    fn corners(self, other: Self, combine: impl Fn(i64, i64) -> i64) -> Self {
+        let values = [
+            combine(self.min, other.min),
+            combine(self.min, other.max),
+            combine(self.max, other.min),
+            combine(self.max, other.max),
+        ];
+        Self {
+            min: *values.iter().min().unwrap(),
+            max: *values.iter().max().unwrap(),
+        }
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// The values that `a op b` might take, or `None` if the instruction always faults.
+    ///
+    /// Results too large for 64 bits fault, so the bounds here saturate.
+    pub fn apply(operation: Operation, a: Self, b: Self) -> Option<Self> {
+        let range = match operation {
+            Operation::Add => Self {
+                min: a.min.saturating_add(b.min),
+                max: a.max.saturating_add(b.max),
+            },
+            Operation::Mul => a.corners(b, i64::saturating_mul),
+            Operation::Div => {
+                // Dividing is monotonic in each argument, so long as the divisor doesn't change
+                // sign.
+                let negative = Self::new(b.min, b.max.min(-1));
+                let positive = Self::new(b.min.max(1), b.max);
+                let quotients = [negative, positive]
+                    .into_iter()
+                    .flatten()
+                    .map(|divisor| a.corners(divisor, i64::saturating_div));
+                quotients.reduce(Self::union)?
+            }
+            Operation::Mod => {
+                let a = Self::new(a.min.max(0), a.max)?;
+                let b = Self::new(b.min.max(1), b.max)?;
+                if a.max < b.min {
+                    a
+                } else if b.min == b.max && a.min / b.min == a.max / b.min {
+                    Self {
+                        min: a.min % b.min,
+                        max: a.max % b.min,
+                    }
+                } else {
+                    Self {
+                        min: 0,
+                        max: a.max.min(b.max - 1),
+                    }
+                }
+            }
+            Operation::Eql => {
+                if a.max < b.min || b.max < a.min {
+                    Self::single(0)
+                } else if a.min == a.max && a == b {
+                    Self::single(1)
+                } else {
+                    Self { min: 0, max: 1 }
+                }
+            }
+        };
+        Some(range)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..={}", self.min, self.max)
+    }
+}
+
+/// A program for the ALU.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+}
+
+impl FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let instructions = parse_lines(s, str::parse)?;
+        Ok(Self { instructions })
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+impl Program {
+    /// Run the program from all-zero registers, reading `input` one value per `inp` instruction.
+    ///
+    /// Any input left over at the end is ignored.
+    pub fn run(&self, input: impl IntoIterator<Item = i64>) -> Result<Registers, Fault> {
+        let mut input = input.into_iter();
+        let mut registers = Registers::default();
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            let fault = |kind| Fault {
+                instruction: index,
+                kind,
+            };
+            match *instruction {
+                Instruction::Inp(register) => {
+                    let value = input.next().ok_or_else(|| fault(FaultKind::EndOfInput))?;
+                    registers.set(register, value);
+                }
+                Instruction::Binary(operation, register, operand) => {
+                    registers
+                        .apply(operation, register, operand)
+                        .map_err(fault)?;
+                }
+            }
+        }
+        Ok(registers)
+    }
+
+    /// What each register might hold at the end of the program, if it carries on from instruction
+    /// `start` with the given registers and reads values in `input` for each `inp`.
+    ///
+    /// `None` means that the program is sure to fault.
+    pub fn ranges(&self, start: usize, registers: &Registers, input: Range) -> Option<[Range; 4]> {
+        let mut ranges = registers.0.map(Range::single);
+        for instruction in &self.instructions[start..] {
+            match *instruction {
+                Instruction::Inp(register) => ranges[register.index()] = input,
+                Instruction::Binary(operation, register, operand) => {
+                    let b = match operand {
+                        Operand::Register(other) => ranges[other.index()],
+                        Operand::Number(number) => Range::single(number),
+                    };
+                    let a = &mut ranges[register.index()];
+                    *a = Range::apply(operation, *a, b)?;
+                }
+            }
+        }
+        Some(ranges)
+    }
+
+    // Which registers might be read before they are overwritten, just before each instruction.
+    // Only `z` matters at the end of the program.
+    fn liveness(&self) -> Vec<[bool; 4]> {
+        let mut live = [false, false, false, true];
+        let mut result = vec![live; self.instructions.len()];
+        for (index, instruction) in self.instructions.iter().enumerate().rev() {
+            match *instruction {
+                Instruction::Inp(register) => live[register.index()] = false,
+                // Multiplying by zero doesn't depend on the register at all.
+                Instruction::Binary(Operation::Mul, register, Operand::Number(0)) => {
+                    live[register.index()] = false
+                }
+                // Anything else might fault, depending on both its arguments.
+                Instruction::Binary(_, register, operand) => {
+                    live[register.index()] = true;
+                    if let Operand::Register(other) = operand {
+                        live[other.index()] = true;
+                    }
+                }
+            }
+            result[index] = live;
+        }
+        result
+    }
+
+    /// The largest or smallest input, reading a digit from 1 to 9 at each `inp`, for which the
+    /// program finishes without faulting and with zero in `z`.
+    ///
+    /// This tries digits one at a time, giving up on any state from which range analysis shows
+    /// that `z` cannot end up zero, or which has already been found to lead nowhere. States that
+    /// differ only in registers that are overwritten before being read count as the same.
+    pub fn search_digits(&self, largest: bool) -> Option<Vec<i64>> {
+        let digits = if largest {
+            [9, 8, 7, 6, 5, 4, 3, 2, 1]
+        } else {
+            [1, 2, 3, 4, 5, 6, 7, 8, 9]
+        };
+        let mut search = DigitSearch {
+            program: self,
+            digits,
+            live: self.liveness(),
+            dead_ends: HashSet::new(),
+        };
+        let mut input = vec![];
+        search
+            .explore(0, Registers::default(), &mut input)
+            .then_some(input)
+    }
+}
+
+struct DigitSearch<'a> {
+    program: &'a Program,
+    // The order in which to try digits.
+    digits: [i64; 9],
+    // The registers that matter just before each instruction.
+    live: Vec<[bool; 4]>,
+    // States, as the index of an `inp` instruction and the registers just before it, from which
+    // no input is accepted.
+    dead_ends: HashSet<(usize, Registers)>,
+}
+
+impl DigitSearch<'_> {
+    // Whether the program accepts some input from here, adding the digits of the first one found
+    // to `input`.
+    fn explore(
+        &mut self,
+        mut index: usize,
+        mut registers: Registers,
+        input: &mut Vec<i64>,
+    ) -> bool {
+        // Run up to the next input.
+        let target = loop {
+            match self.program.instructions.get(index) {
+                None => return registers.get(Register::Z) == 0,
+                Some(&Instruction::Inp(register)) => break register,
+                Some(&Instruction::Binary(operation, register, operand)) => {
+                    if registers.apply(operation, register, operand).is_err() {
+                        return false;
+                    }
+                    index += 1;
+                }
+            }
+        };
+
+        // Forget anything that makes no difference from here on.
+        for register in Register::ALL {
+            if !self.live[index][register.index()] {
+                registers.set(register, 0);
+            }
+        }
+        if self.dead_ends.contains(&(index, registers)) {
+            return false;
+        }
+        let digit_range = Range { min: 1, max: 9 };
+        let possible = self
+            .program
+            .ranges(index, &registers, digit_range)
+            .is_some_and(|ranges| ranges[Register::Z.index()].contains(0));
+        if possible {
+            for digit in self.digits {
+                let mut next = registers;
+                next.set(target, digit);
+                input.push(digit);
+                if self.explore(index + 1, next, input) {
+                    return true;
+                }
+                input.pop();
+            }
+        }
+
+        self.dead_ends.insert((index, registers));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Store the binary digits of the input in w, x, y and z, most significant first.
+    const BINARY: &str = "\
+inp w
+add z w
+mod z 2
+div w 2
+add y w
+mod y 2
+div w 2
+add x w
+mod x 2
+div w 2
+mod w 2
+";
+
+    #[test]
+    fn examples() {
+        let negate: Program = "inp x\nmul x -1\n".parse().unwrap();
+        let registers = negate.run([7]).unwrap();
+        assert_eq!(registers.get(Register::X), -7);
+
+        let three_times: Program = "inp z\ninp x\nmul z 3\neql z x\n".parse().unwrap();
+        assert_eq!(three_times.run([2, 6]).unwrap().get(Register::Z), 1);
+        assert_eq!(three_times.run([2, 7]).unwrap().get(Register::Z), 0);
+
+        let binary: Program = BINARY.parse().unwrap();
+        let registers = binary.run([13]).unwrap();
+        let bits = Register::ALL.map(|register| registers.get(register));
+        assert_eq!(bits, [1, 1, 0, 1]);
+        assert_eq!(binary.to_string(), BINARY);
+    }
+
+    #[test]
+    fn faults() {
+        let program: Program = "inp x\ninp y\ndiv x y\n".parse().unwrap();
+        let fault = program.run([1, 0]).unwrap_err();
+        assert_eq!(fault.kind, FaultKind::DivideByZero);
+        assert_eq!(fault.to_string(), "instruction 3: division by zero");
+        assert_eq!(program.run([1]).unwrap_err().kind, FaultKind::EndOfInput);
+
+        let program: Program = "inp x\nmod x 5\n".parse().unwrap();
+        assert_eq!(
+            program.run([-3]).unwrap_err().kind,
+            FaultKind::NegativeModulo
+        );
+        assert_eq!(program.run([13]).unwrap().get(Register::X), 3);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = "inp w\nadd q 1\n".parse::<Program>().unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(5)));
+        let error = "inp w\nsub w 1\n".parse::<Program>().unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(1)));
+        assert!("inp w x\n".parse::<Program>().is_err());
+        assert!("mul x\n".parse::<Program>().is_err());
+    }
+
+    #[test]
+    fn range_arithmetic() {
+        let range = |min, max| Range { min, max };
+        let apply = |operation, a, b| Range::apply(operation, a, b);
+        assert_eq!(
+            apply(Operation::Mul, range(-2, 3), range(4, 5)),
+            Some(range(-10, 15))
+        );
+        assert_eq!(
+            apply(Operation::Div, range(10, 20), range(-2, 5)),
+            Some(range(-20, 20))
+        );
+        assert_eq!(apply(Operation::Div, range(10, 20), range(0, 0)), None);
+        assert_eq!(
+            apply(Operation::Mod, range(27, 30), Range::single(26)),
+            Some(range(1, 4))
+        );
+        assert_eq!(
+            apply(Operation::Mod, range(20, 30), Range::single(26)),
+            Some(range(0, 25))
+        );
+        assert_eq!(apply(Operation::Mod, range(-5, -1), range(1, 3)), None);
+        assert_eq!(
+            apply(Operation::Eql, range(10, 20), range(1, 9)),
+            Some(Range::single(0))
+        );
+    }
+
+    #[test]
+    fn search() {
+        // Accept two digits adding up to ten.
+        let program: Program = "inp w\ninp y\nadd w y\neql w 10\nadd z 1\nmul w -1\nadd z w\n"
+            .parse()
+            .unwrap();
+        assert_eq!(program.search_digits(true), Some(vec![9, 1]));
+        assert_eq!(program.search_digits(false), Some(vec![1, 9]));
+
+        // Dividing by the second digit minus one faults when it's one.
+        let program: Program = "inp w\ninp z\nadd z -1\ndiv w z\nadd z -1\n"
+            .parse()
+            .unwrap();
+        assert_eq!(program.search_digits(false), Some(vec![1, 2]));
+
+        let program: Program = "inp z\nadd z 10\n".parse().unwrap();
+        assert_eq!(program.search_digits(true), None);
+    }
+
+    #[test]
+    fn liveness() {
+        let program: Program = "inp w\nmul x 0\nadd x w\nmul y 0\nadd z x\n"
+            .parse()
+            .unwrap();
+        let live = program.liveness();
+        assert_eq!(live[0], [false, false, false, true]);
+        assert_eq!(live[2], [true, true, false, true]);
+        assert_eq!(live[3], [false, true, false, true]);
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Answer {
+    Number(i128),
+    // Some answers, such as day 13's folded letters, are pictures rather than numbers.
+    Text(String),
+    // There is no second puzzle on the last day, and some inputs have no answer.
+    Nothing,
+    // The input can't be solved, for the given reason.
+    Unsolvable(String),
+}
+
+macro_rules! number_answer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Self::Number(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+number_answer!(u32, u64, usize, i64, isize);
+
+impl From<u128> for Answer {
+    fn from(value: u128) -> Self {
+        // Too large to be a number, but the digits are still the answer.
+        i128::try_from(value).map_or_else(|_| Self::Text(value.to_string()), Self::Number)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<()> for Answer {
+    fn from(_: ()) -> Self {
+        Self::Nothing
+    }
+}
+
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Nothing, Into::into)
+    }
+}
+
+impl<T: Into<Answer>, E: fmt::Display> From<Result<T, E>> for Answer {
+    fn from(value: Result<T, E>) -> Self {
+        value.map_or_else(|error| Self::Unsolvable(error.to_string()), Into::into)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{}", value),
+            Self::Text(text) => write!(f, "{}", text),
+            Self::Nothing => write!(f, "-"),
+            Self::Unsolvable(reason) => write!(f, "unsolvable: {}", reason),
+        }
+    }
+}
+
+/// The answers to both parts of a puzzle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Answers {
+    pub part_one: Answer,
+    pub part_two: Answer,
+}
@@ -1,13 +1,5 @@
-pub(crate) fn day01() {
-    let input = std::fs::read_to_string("data/day01.txt").unwrap();
-    let readings: Vec<usize> = input.lines().map(|line| line.parse().unwrap()).collect();
-
-    let increases = count_increases(&readings, 1);
-    println!("Part one answer is {}", increases);
-
-    let increases = count_increases(&readings, 3);
-    println!("Part two answer is {}", increases);
-}
+use crate::error::{parse_lines, parse_word};
+use crate::{ParseError, Solution};
 
 fn count_increases(input: &[usize], step: usize) -> usize {
     input
@@ -16,3 +8,65 @@ fn count_increases(input: &[usize], step: usize) -> usize {
         .filter(|(prev, next)| prev < next)
         .count()
 }
+
+const EXAMPLE: &str = "\
+199
+200
+208
+210
+200
+207
+240
+269
+260
+263
+";
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<usize>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let readings = parse_lines(input, |line| parse_word(line, line, "a depth"))?;
+        // Part two compares sliding windows of three, so needs at least two of them.
+        if readings.len() < 4 {
+            return Err(ParseError::new(format!(
+                "expected at least 4 depths, found {}",
+                readings.len()
+            )));
+        }
+        Ok(readings)
+    }
+
+    fn part_one(readings: &Self::Input) -> Self::PartOne {
+        count_increases(readings, 1)
+    }
+
+    fn part_two(readings: &Self::Input) -> Self::PartTwo {
+        count_increases(readings, 3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day01::parse(EXAMPLE).unwrap();
+        assert_eq!(Day01::part_one(&input), 7);
+        assert_eq!(Day01::part_two(&input), 5);
+    }
+
+    #[test]
+    fn too_few_depths() {
+        let error = Day01::parse("199\n200\n208\n").unwrap_err();
+        assert_eq!(error.message, "expected at least 4 depths, found 3");
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::{next_word, parse_lines};
+use crate::{ParseError, Solution};
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -7,9 +9,6 @@ enum Direction {
     Up,
 }
 
-#[derive(Debug)]
-struct ParseError;
-
 impl FromStr for Direction {
     type Err = ParseError;
 
@@ -18,26 +17,22 @@ impl FromStr for Direction {
             "forward" => Self::Forward,
             "down" => Self::Down,
             "up" => Self::Up,
-            _ => return Err(ParseError),
+            _ => return Err(ParseError::new(format!("unknown direction `{}`", s))),
         };
         Ok(direction)
     }
 }
 
 #[derive(Debug)]
-struct Instruction(Direction, usize);
+pub struct Instruction(Direction, usize);
 
 impl FromStr for Instruction {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut words = s.split_whitespace();
-        let direction: Direction = words.next().ok_or(ParseError).and_then(|w| w.parse())?;
-
-        let distance: usize = words
-            .next()
-            .and_then(|w| w.parse().ok())
-            .ok_or(ParseError)?;
+        let direction = next_word(s, &mut words, "`forward`, `down` or `up`")?;
+        let distance = next_word(s, &mut words, "a distance")?;
 
         Ok(Instruction(direction, distance))
     }
@@ -66,19 +61,54 @@ fn move_submarine_part_two(
     }
 }
 
-pub(crate) fn day02() {
-    let input = std::fs::read_to_string("data/day02.txt").unwrap();
-    let instructions: Vec<Instruction> = input.lines().map(|line| line.parse().unwrap()).collect();
+const EXAMPLE: &str = "\
+forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+
+pub struct Day02;
 
-    let finish = instructions.iter().fold((0, 0), |position, instruction| {
-        move_submarine_part_one(&position, instruction)
-    });
-    println!("Part one answer is {}", finish.0 * finish.1);
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<Instruction>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_lines(input, str::parse)
+    }
 
-    let finish = instructions
-        .iter()
-        .fold((0, 0, 0), |position, instruction| {
-            move_submarine_part_two(&position, instruction)
+    fn part_one(instructions: &Self::Input) -> Self::PartOne {
+        let finish = instructions.iter().fold((0, 0), |position, instruction| {
+            move_submarine_part_one(&position, instruction)
         });
-    println!("Part two answer is {}", finish.0 * finish.1);
+        finish.0 * finish.1
+    }
+
+    fn part_two(instructions: &Self::Input) -> Self::PartTwo {
+        let finish = instructions
+            .iter()
+            .fold((0, 0, 0), |position, instruction| {
+                move_submarine_part_two(&position, instruction)
+            });
+        finish.0 * finish.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day02::parse(EXAMPLE).unwrap();
+        assert_eq!(Day02::part_one(&input), 150);
+        assert_eq!(Day02::part_two(&input), 900);
+    }
 }
@@ -1,3 +1,6 @@
+use crate::error::parse_lines;
+use crate::{ParseError, Solution};
+
 fn count_ones(numbers: &[usize], bit: usize) -> usize {
     numbers
         .iter()
@@ -12,6 +15,10 @@ fn apply_criteria(mut numbers: Vec<usize>, bit: usize, oxygen: bool) -> Vec<usiz
     }
 
     let ones = count_ones(&numbers, bit);
+    // If every number has the same bit here, there's nothing to choose between.
+    if ones == 0 || ones == length {
+        return numbers;
+    }
 
     let keep_ones = if oxygen {
         2 * ones >= length
@@ -23,31 +30,98 @@ fn apply_criteria(mut numbers: Vec<usize>, bit: usize, oxygen: bool) -> Vec<usiz
     numbers
 }
 
-pub(crate) fn day03() {
-    let input = std::fs::read_to_string("data/day03.txt").unwrap();
-    let numbers: Vec<usize> = input
-        .lines()
-        .map(|line| usize::from_str_radix(line, 2).unwrap())
-        .collect();
-    let length = numbers.len();
+pub struct DiagnosticReport {
+    numbers: Vec<usize>,
+    bits: usize,
+}
+
+const EXAMPLE: &str = "\
+00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+";
 
-    let mut gamma = 0;
-    let mut epsilon = 0;
-    for bit in 0..12 {
-        let ones = count_ones(&numbers, bit);
-        if 2 * ones > length {
-            gamma |= 1 << bit
-        } else {
-            epsilon |= 1 << bit
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = DiagnosticReport;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let numbers = parse_lines(input, |line| {
+            usize::from_str_radix(line, 2)
+                .map_err(|_| ParseError::new(format!("expected a binary number, found `{}`", line)))
+        })?;
+        if numbers.is_empty() {
+            return Err(ParseError::new(
+                "expected binary numbers, found empty input",
+            ));
         }
+        let bits = input.lines().map(str::len).max().unwrap_or(0);
+        let report = DiagnosticReport { numbers, bits };
+        Ok(report)
+    }
+
+    fn part_one(report: &Self::Input) -> Self::PartOne {
+        let numbers = &report.numbers;
+        let length = numbers.len();
+        let mut gamma = 0;
+        let mut epsilon = 0;
+        for bit in 0..report.bits {
+            let ones = count_ones(numbers, bit);
+            if 2 * ones > length {
+                gamma |= 1 << bit
+            } else {
+                epsilon |= 1 << bit
+            }
+        }
+        gamma * epsilon
+    }
+
+    fn part_two(report: &Self::Input) -> Self::PartTwo {
+        let mut oxygen_set = report.numbers.clone();
+        let mut co2_set = report.numbers.clone();
+        for bit in (0..report.bits).rev() {
+            oxygen_set = apply_criteria(oxygen_set, bit, true);
+            co2_set = apply_criteria(co2_set, bit, false);
+        }
+        oxygen_set[0] * co2_set[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day03::parse(EXAMPLE).unwrap();
+        assert_eq!(Day03::part_one(&input), 198);
+        assert_eq!(Day03::part_two(&input), 230);
+    }
+
+    #[test]
+    fn empty() {
+        let error = Day03::parse("").err().unwrap();
+        assert_eq!(error.message, "expected binary numbers, found empty input");
     }
-    println!("Part one answer is {}", gamma * epsilon);
 
-    let mut oxygen_set = numbers.clone();
-    let mut co2_set = numbers;
-    for bit in (0..12).rev() {
-        oxygen_set = apply_criteria(oxygen_set, bit, true);
-        co2_set = apply_criteria(co2_set, bit, false);
+    #[test]
+    fn repeated_number() {
+        let input = Day03::parse("10110\n10110\n").unwrap();
+        assert_eq!(Day03::part_two(&input), 22 * 22);
     }
-    println!("Part two answer is {}", oxygen_set[0] * co2_set[0]);
 }
@@ -1,6 +1,6 @@
-use itertools::Itertools;
+use crate::error::parse_word;
+use crate::{ParseError, Solution};
 use std::collections::{HashMap, HashSet};
-use std::num::ParseIntError;
 use std::str::FromStr;
 
 #[derive(Clone, Debug)]
@@ -49,13 +49,14 @@ impl Board {
 }
 
 impl FromStr for Board {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut numbers: HashMap<usize, (usize, usize)> = HashMap::new();
         for (row, line) in s.lines().enumerate() {
             for (column, word) in line.split_whitespace().enumerate() {
-                let number: usize = word.parse()?;
+                let number: usize =
+                    parse_word(line, word, "a number").map_err(|error| error.line_offset(row))?;
                 numbers.insert(number, (column, row));
             }
         }
@@ -64,51 +65,141 @@ impl FromStr for Board {
     }
 }
 
-pub(crate) fn day04() {
-    let input = std::fs::read_to_string("data/day04.txt").unwrap();
-    let mut lines = input.lines();
-    let numbers: Vec<usize> = lines
-        .next()
-        .unwrap()
-        .split(',')
-        .map(|word| word.parse().unwrap())
-        .collect();
-
-    let boards: Vec<Board> = lines
-        .chunks(6)
-        .into_iter()
-        .map(|mut chunk| {
-            let _blank = chunk.next();
-            chunk.join("\n").parse().unwrap()
-        })
-        .collect();
-
-    let mut part_one_boards = boards.clone();
-    'outer1: for number in &numbers {
-        for board in &mut part_one_boards {
-            board.see(*number);
-            if board.is_winner() {
-                println!("Part one answer is {}", board.score(*number));
-                break 'outer1;
+pub struct Bingo {
+    numbers: Vec<usize>,
+    boards: Vec<Board>,
+}
+
+const EXAMPLE: &str = "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Bingo;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let lines: Vec<&str> = input.lines().collect();
+        let (first, rest) = lines
+            .split_first()
+            .ok_or_else(|| ParseError::new("expected numbers to call, found empty input"))?;
+        let numbers: Vec<usize> = first
+            .split(',')
+            .map(|word| parse_word(first, word, "a number to call"))
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.line_offset(0))?;
+
+        // Each board is preceded by a blank line.
+        let boards: Vec<Board> = rest
+            .chunks(6)
+            .enumerate()
+            .map(|(index, chunk)| {
+                chunk[1..]
+                    .join("\n")
+                    .parse()
+                    .map_err(|error: ParseError| error.line_offset(2 + 6 * index))
+            })
+            .collect::<Result<_, _>>()?;
+        if boards.is_empty() {
+            return Err(ParseError::new("expected boards, found none").line_offset(1));
+        }
+
+        // Both parts wait for boards to win, so every board must win eventually.
+        for (index, board) in boards.iter().enumerate() {
+            let mut board = board.clone();
+            if !numbers.iter().any(|number| {
+                board.see(*number);
+                board.is_winner()
+            }) {
+                let error = ParseError::new("expected a board that can win, found one that can't");
+                return Err(error.line_offset(2 + 6 * index));
             }
         }
+
+        let bingo = Bingo { numbers, boards };
+        Ok(bingo)
     }
 
-    let mut winners: HashSet<usize> = HashSet::new();
-    let mut part_two_boards = boards.clone();
-    'outer2: for number in &numbers {
-        for (index, board) in part_two_boards.iter_mut().enumerate() {
-            if winners.contains(&index) {
-                continue;
+    fn part_one(bingo: &Self::Input) -> Self::PartOne {
+        let mut boards = bingo.boards.clone();
+        for number in &bingo.numbers {
+            for board in &mut boards {
+                board.see(*number);
+                if board.is_winner() {
+                    return board.score(*number);
+                }
             }
-            board.see(*number);
-            if board.is_winner() {
-                winners.insert(index);
-                if winners.len() == boards.len() {
-                    println!("Part two answer is {}", board.score(*number));
-                    break 'outer2;
+        }
+        unreachable!()
+    }
+
+    fn part_two(bingo: &Self::Input) -> Self::PartTwo {
+        let mut winners: HashSet<usize> = HashSet::new();
+        let mut boards = bingo.boards.clone();
+        for number in &bingo.numbers {
+            for (index, board) in boards.iter_mut().enumerate() {
+                if winners.contains(&index) {
+                    continue;
+                }
+                board.see(*number);
+                if board.is_winner() {
+                    winners.insert(index);
+                    if winners.len() == bingo.boards.len() {
+                        return board.score(*number);
+                    }
                 }
             }
         }
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day04::parse(EXAMPLE).unwrap();
+        assert_eq!(Day04::part_one(&input), 4512);
+        assert_eq!(Day04::part_two(&input), 1924);
+    }
+
+    #[test]
+    fn no_boards() {
+        let error = Day04::parse("7,4,9\n").err().unwrap();
+        assert_eq!(error.message, "expected boards, found none");
+    }
+
+    #[test]
+    fn losing_board() {
+        let (numbers, boards) = EXAMPLE.split_once('\n').unwrap();
+        let input = format!("{}\n{}", &numbers[..10], boards);
+        let error = Day04::parse(&input).err().unwrap();
+        assert_eq!(error.line, Some(3));
     }
 }
@@ -1,3 +1,5 @@
+use crate::error::{expect_word, next_word, parse_lines};
+use crate::{ParseError, Solution};
 use std::collections::HashMap;
 use std::str::FromStr;
 
@@ -22,9 +24,6 @@ impl Iterator for Stepper {
     }
 }
 
-#[derive(Debug)]
-struct ParseError;
-
 #[derive(PartialEq, Eq, Debug, Hash, Copy, Clone)]
 struct Point(isize, isize);
 
@@ -33,21 +32,15 @@ impl FromStr for Point {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut words = s.split(',');
-        let x = words
-            .next()
-            .and_then(|w| w.parse().ok())
-            .ok_or(ParseError)?;
-        let y = words
-            .next()
-            .and_then(|w| w.parse().ok())
-            .ok_or(ParseError)?;
+        let x = next_word(s, &mut words, "an x coordinate")?;
+        let y = next_word(s, &mut words, "a y coordinate")?;
         let point = Point(x, y);
         Ok(point)
     }
 }
 
 #[derive(Debug)]
-struct Line {
+pub struct Line {
     end1: Point,
     end2: Point,
 }
@@ -80,37 +73,72 @@ impl FromStr for Line {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut words = s.split(' ');
-        let end1: Point = words.next().ok_or(ParseError).and_then(|w| w.parse())?;
-        let _arrow = words.next();
-        let end2: Point = words.next().ok_or(ParseError).and_then(|w| w.parse())?;
+        let end1 = next_word(s, &mut words, "a point `x,y`")?;
+        expect_word(s, &mut words, "->")?;
+        let end2 = next_word(s, &mut words, "a point `x,y`")?;
         let line = Line::new(end1, end2);
         Ok(line)
     }
 }
 
-pub(crate) fn day05() {
-    let input = std::fs::read_to_string("data/day05.txt").unwrap();
-    let (rectilinear, diagonals): (Vec<Line>, Vec<Line>) = input
-        .lines()
-        .map(|line| line.parse::<Line>().unwrap())
-        .partition(|line| line.is_vertical() || line.is_horizontal());
-
+fn count_overlaps<'a>(lines: impl Iterator<Item = &'a Line>) -> usize {
     let mut grid: HashMap<Point, usize> = HashMap::new();
-    for line in &rectilinear {
+    for line in lines {
         for point in line.points() {
             let counter = grid.entry(point).or_insert(0);
             *counter += 1;
         }
     }
-    let count = grid.values().filter(|&&count| count > 1).count();
-    println!("Part one answer is {}", count);
+    grid.values().filter(|&&count| count > 1).count()
+}
 
-    for line in &diagonals {
-        for point in line.points() {
-            let counter = grid.entry(point).or_insert(0);
-            *counter += 1;
-        }
+const EXAMPLE: &str = "\
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+";
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<Line>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_lines(input, str::parse)
+    }
+
+    fn part_one(lines: &Self::Input) -> Self::PartOne {
+        let rectilinear = lines
+            .iter()
+            .filter(|line| line.is_vertical() || line.is_horizontal());
+        count_overlaps(rectilinear)
+    }
+
+    fn part_two(lines: &Self::Input) -> Self::PartTwo {
+        count_overlaps(lines.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day05::parse(EXAMPLE).unwrap();
+        assert_eq!(Day05::part_one(&input), 5);
+        assert_eq!(Day05::part_two(&input), 12);
     }
-    let count = grid.values().filter(|&&count| count > 1).count();
-    println!("Part two answer is {}", count);
 }
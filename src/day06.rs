@@ -1,3 +1,5 @@
+use crate::error::parse_word;
+use crate::{ParseError, Solution};
 use std::collections::HashMap;
 
 fn update_population(population: HashMap<usize, usize>) -> HashMap<usize, usize> {
@@ -13,29 +15,60 @@ fn update_population(population: HashMap<usize, usize>) -> HashMap<usize, usize>
     new
 }
 
-pub(crate) fn day06() {
-    let input = std::fs::read_to_string("data/day06.txt").unwrap();
-    let fish: Vec<usize> = input
-        .trim()
-        .split(',')
-        .map(|word| word.parse().unwrap())
-        .collect();
-
-    let mut counts: HashMap<usize, usize> = HashMap::new();
-    for f in fish {
-        let counter = counts.entry(f).or_insert(0);
-        *counter += 1;
-    }
-
-    let part_one = (0..80).fold(counts.clone(), |population, _day| {
+fn population_after(counts: &HashMap<usize, usize>, days: usize) -> usize {
+    let population = (0..days).fold(counts.clone(), |population, _day| {
         update_population(population)
     });
-    let total: usize = part_one.values().sum();
-    println!("Part one answer is {}", total);
+    population.values().sum()
+}
 
-    let part_two = (0..256).fold(counts.clone(), |population, _day| {
-        update_population(population)
-    });
-    let total: usize = part_two.values().sum();
-    println!("Part two answer is {}", total);
+const EXAMPLE: &str = "\
+3,4,3,1,2
+";
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = HashMap<usize, usize>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let line = input.trim_end();
+        let fish: Vec<usize> = line
+            .split(',')
+            .map(|word| parse_word(line, word, "a timer"))
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.line_offset(0))?;
+
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for f in fish {
+            let counter = counts.entry(f).or_insert(0);
+            *counter += 1;
+        }
+        Ok(counts)
+    }
+
+    fn part_one(counts: &Self::Input) -> Self::PartOne {
+        population_after(counts, 80)
+    }
+
+    fn part_two(counts: &Self::Input) -> Self::PartTwo {
+        population_after(counts, 256)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day06::parse(EXAMPLE).unwrap();
+        assert_eq!(Day06::part_one(&input), 5934);
+        assert_eq!(Day06::part_two(&input), 26984457539);
+    }
 }
@@ -1,46 +1,69 @@
-fn part_one(positions: &[isize]) -> isize {
-    // Any median will do.
-    let mut sorted = positions.to_vec();
-    sorted.sort_unstable();
-    let length = positions.len();
-    let middle = length / 2;
-    let median = sorted[middle];
-    positions
-        .iter()
-        .map(|position| (position - median).abs())
-        .sum()
-}
+use crate::error::parse_word;
+use crate::{ParseError, Solution};
 
-fn part_two(positions: &[isize]) -> isize {
-    // Solution is near the mean.
-    let length = positions.len();
-    let sum: isize = positions.iter().sum();
-    let mean = sum / (length as isize);
-    (mean..=mean + 1)
-        .map(|m| {
-            positions
-                .iter()
-                .map(|position| {
-                    let distance = (position - m).abs();
-                    (distance * (distance + 1)) / 2
-                })
-                .sum()
-        })
-        .min()
-        .unwrap()
-}
+const EXAMPLE: &str = "\
+16,1,2,0,4,2,7,1,2,14
+";
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
 
-pub(crate) fn day07() {
-    let input = std::fs::read_to_string("data/day07.txt").unwrap();
-    let positions: Vec<isize> = input
-        .trim()
-        .split(',')
-        .map(|word| word.parse().unwrap())
-        .collect();
+    type Input = Vec<isize>;
+    type PartOne = isize;
+    type PartTwo = isize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let line = input.trim_end();
+        line.split(',')
+            .map(|word| parse_word(line, word, "a position"))
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.line_offset(0))
+    }
+
+    fn part_one(positions: &Self::Input) -> Self::PartOne {
+        // Any median will do.
+        let mut sorted = positions.to_vec();
+        sorted.sort_unstable();
+        let length = positions.len();
+        let middle = length / 2;
+        let median = sorted[middle];
+        positions
+            .iter()
+            .map(|position| (position - median).abs())
+            .sum()
+    }
+
+    fn part_two(positions: &Self::Input) -> Self::PartTwo {
+        // Solution is near the mean.
+        let length = positions.len();
+        let sum: isize = positions.iter().sum();
+        let mean = sum / (length as isize);
+        (mean..=mean + 1)
+            .map(|m| {
+                positions
+                    .iter()
+                    .map(|position| {
+                        let distance = (position - m).abs();
+                        (distance * (distance + 1)) / 2
+                    })
+                    .sum()
+            })
+            .min()
+            .unwrap()
+    }
+}
 
-    let cost = part_one(&positions);
-    println!("Part one answer is {}", cost);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let cost = part_two(&positions);
-    println!("Part two answer is {}", cost);
+    #[test]
+    fn example() {
+        let input = Day07::parse(EXAMPLE).unwrap();
+        assert_eq!(Day07::part_one(&input), 37);
+        assert_eq!(Day07::part_two(&input), 168);
+    }
 }
@@ -1,8 +1,10 @@
+use crate::error::{offset, parse_lines};
+use crate::{ParseError, Solution};
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 #[derive(Debug)]
-struct Note {
+pub struct Note {
     // NB we alphabetize the patterns and output during parsing.
     patterns: [String; 10],
     output: [String; 4],
@@ -76,53 +78,90 @@ impl Note {
     }
 }
 
-#[derive(Debug)]
-struct ParseError;
+// Alphabetize each of the words in a section of a note.
+fn read_section<const N: usize>(line: &str, section: &str) -> Result<[String; N], ParseError> {
+    let words: Vec<String> = section
+        .split_whitespace()
+        .map(|word| {
+            let mut letters = word.chars().collect::<Vec<_>>();
+            letters.sort_unstable();
+            letters.into_iter().collect()
+        })
+        .collect();
+    let count = words.len();
+    words.try_into().map_err(|_| {
+        ParseError::new(format!("expected {} patterns, found {}", N, count))
+            .column_offset(offset(line, section))
+    })
+}
 
 impl FromStr for Note {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut patterns: [String; 10] = Default::default();
-        let mut output: [String; 4] = Default::default();
-
-        let mut sections = s.split('|');
-        let inputs = sections.next().ok_or(ParseError)?;
-        for (index, word) in inputs.split_whitespace().enumerate() {
-            let mut letters = word.chars().collect::<Vec<_>>();
-            letters.sort_unstable();
-            patterns[index] = letters.into_iter().collect();
-        }
-        let outputs = sections.next().ok_or(ParseError)?;
-        for (index, word) in outputs.split_whitespace().enumerate() {
-            let mut letters = word.chars().collect::<Vec<_>>();
-            letters.sort_unstable();
-            output[index] = letters.into_iter().collect();
-        }
+        let (inputs, outputs) = s
+            .split_once('|')
+            .ok_or_else(|| ParseError::new("expected `|` between patterns and output"))?;
+        let patterns = read_section(s, inputs)?;
+        let output = read_section(s, outputs)?;
 
         let note = Note::new(patterns, output);
         Ok(note)
     }
 }
 
-pub(crate) fn day08() {
-    let notes: Vec<Note> = std::fs::read_to_string("data/day08.txt")
-        .unwrap()
-        .lines()
-        .map(|line| line.parse().unwrap())
-        .collect();
+const EXAMPLE: &str = "\
+be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgdbac fcadb gbdfca | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+";
 
-    let easy: usize = notes
-        .iter()
-        .map(|note| {
-            note.output
-                .iter()
-                .filter(|digit| matches!(digit.len(), 2 | 3 | 4 | 7))
-                .count()
-        })
-        .sum();
-    println!("Part one answer is {}", easy);
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<Note>;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let total: usize = notes.iter().map(|note| note.solve()).sum();
-    println!("Part two answer is {}", total);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_lines(input, str::parse)
+    }
+
+    fn part_one(notes: &Self::Input) -> Self::PartOne {
+        notes
+            .iter()
+            .map(|note| {
+                note.output
+                    .iter()
+                    .filter(|digit| matches!(digit.len(), 2 | 3 | 4 | 7))
+                    .count()
+            })
+            .sum()
+    }
+
+    fn part_two(notes: &Self::Input) -> Self::PartTwo {
+        notes.iter().map(|note| note.solve()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day08::parse(EXAMPLE).unwrap();
+        assert_eq!(Day08::part_one(&input), 26);
+        assert_eq!(Day08::part_two(&input), 61229);
+    }
 }
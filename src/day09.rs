@@ -1,66 +1,42 @@
-use std::collections::{HashMap, HashSet};
+use crate::error::parse_digit;
+use crate::grid::{Grid, Neighbourhood, Point};
+use crate::{ParseError, Solution};
+use std::collections::HashSet;
 use std::str::FromStr;
 
-struct HeightMap {
-    heights: HashMap<(usize, usize), u32>,
-    max_x: usize,
-    max_y: usize,
+pub struct HeightMap {
+    heights: Grid<u32>,
 }
 
 impl FromStr for HeightMap {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut heights: HashMap<(usize, usize), u32> = HashMap::new();
-        for (y, line) in s.lines().enumerate() {
-            for (x, number) in line.chars().enumerate() {
-                let height = number.to_digit(10).ok_or(())?;
-                heights.insert((x, y), height);
-            }
-        }
-
-        let height_map = Self::new(heights);
+        let heights = Grid::parse(s, "a height map", parse_digit)?;
+        let height_map = Self { heights };
         Ok(height_map)
     }
 }
 
 impl HeightMap {
-    fn new(heights: HashMap<(usize, usize), u32>) -> Self {
-        let &max_x = heights.keys().map(|(x, _)| x).max().unwrap();
-        let &max_y = heights.keys().map(|(_, y)| y).max().unwrap();
-        Self {
-            heights,
-            max_x,
-            max_y,
-        }
+    fn neighbours(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.heights.neighbours(point, Neighbourhood::Orthogonal)
     }
 
-    fn neighbours(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
-        let mut neighbours = vec![];
-        if x > 0 {
-            neighbours.push((x - 1, y));
-        }
-        if x < self.max_x {
-            neighbours.push((x + 1, y));
-        }
-        if y > 0 {
-            neighbours.push((x, y - 1));
-        }
-        if y < self.max_y {
-            neighbours.push((x, y + 1));
-        }
-        neighbours
+    fn low_points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.heights
+            .points()
+            .filter(|&point| self.is_low_point(point))
     }
 
-    fn is_low_point(&self, point: (usize, usize)) -> bool {
-        let height = self.heights[&point];
+    fn is_low_point(&self, point: Point) -> bool {
+        let height = self.heights[point];
         self.neighbours(point)
-            .iter()
             .all(|neighbour| self.heights[neighbour] > height)
     }
 
-    fn basin_size(&self, start_point: (usize, usize)) -> usize {
-        let mut basin = HashSet::<(usize, usize)>::new();
+    fn basin_size(&self, start_point: Point) -> usize {
+        let mut basin = HashSet::<Point>::new();
         let mut stack = vec![start_point];
 
         while let Some(point) = stack.pop() {
@@ -69,7 +45,7 @@ impl HeightMap {
             }
 
             for neighbour in self.neighbours(point) {
-                if self.heights[&neighbour] != 9 {
+                if self.heights[neighbour] != 9 {
                     stack.push(neighbour);
                 }
             }
@@ -79,24 +55,54 @@ impl HeightMap {
     }
 }
 
-pub(crate) fn day09() {
-    let input = std::fs::read_to_string("data/day09.txt").unwrap();
-    let height_map: HeightMap = input.parse().unwrap();
-
-    let low_points = itertools::iproduct!((0..=height_map.max_x), (0..=height_map.max_y))
-        .filter(|&point| height_map.is_low_point(point));
-
-    let part_one: u32 = low_points
-        .clone()
-        .map(|point| 1 + height_map.heights[&point])
-        .sum();
-    println!("Part one answer is {}", part_one);
-
-    let mut basin_sizes: Vec<_> = low_points
-        .map(|point| height_map.basin_size(point))
-        .collect();
-    basin_sizes.sort_unstable();
-    basin_sizes.reverse();
-    let part_two: usize = basin_sizes.iter().take(3).product();
-    println!("Part two answer is {}", part_two);
+const EXAMPLE: &str = "\
+2199943210
+3987894921
+9856789892
+8767896789
+9899965678
+";
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = HeightMap;
+    type PartOne = u32;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input.parse()
+    }
+
+    fn part_one(height_map: &Self::Input) -> Self::PartOne {
+        height_map
+            .low_points()
+            .map(|point| 1 + height_map.heights[point])
+            .sum()
+    }
+
+    fn part_two(height_map: &Self::Input) -> Self::PartTwo {
+        let mut basin_sizes: Vec<_> = height_map
+            .low_points()
+            .map(|point| height_map.basin_size(point))
+            .collect();
+        basin_sizes.sort_unstable();
+        basin_sizes.reverse();
+        basin_sizes.iter().take(3).product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day09::parse(EXAMPLE).unwrap();
+        assert_eq!(Day09::part_one(&input), 15);
+        assert_eq!(Day09::part_two(&input), 1134);
+    }
 }
@@ -1,3 +1,5 @@
+use crate::error::parse_lines;
+use crate::{ParseError, Solution};
 use lazy_static::lazy_static;
 use maplit::hashmap;
 use std::collections::HashMap;
@@ -60,18 +62,78 @@ fn completion_score(line: &str) -> Option<usize> {
     Some(score)
 }
 
-pub(crate) fn day10() {
-    let input = std::fs::read_to_string("data/day10.txt").unwrap();
-    let lines: Vec<&str> = input.lines().collect();
+const EXAMPLE: &str = "\
+[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]
+";
 
-    let part_one: usize = lines.iter().filter_map(|line| syntax_score(line)).sum();
-    println!("Part one answer is {}", part_one);
+pub struct Day10;
 
-    let mut completion_scores: Vec<usize> = lines
-        .iter()
-        .filter_map(|line| completion_score(line))
-        .collect();
-    completion_scores.sort_unstable();
-    let part_two = completion_scores[completion_scores.len() / 2];
-    println!("Part two answer is {}", part_two);
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<String>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let lines: Vec<String> = parse_lines(input, |line| {
+            let bad = line.char_indices().find(|(_, c)| !"([{<)]}>".contains(*c));
+            if let Some((column, c)) = bad {
+                let error = ParseError::new(format!("expected a bracket, found `{}`", c));
+                return Err(error.column_offset(column));
+            }
+            Ok(line.to_owned())
+        })?;
+        // Part two takes the middle completion score, so there must be at least one.
+        if lines.iter().all(|line| syntax_score(line).is_some()) {
+            return Err(ParseError::new(
+                "expected a line that isn't corrupted, found none",
+            ));
+        }
+        Ok(lines)
+    }
+
+    fn part_one(lines: &Self::Input) -> Self::PartOne {
+        lines.iter().filter_map(|line| syntax_score(line)).sum()
+    }
+
+    fn part_two(lines: &Self::Input) -> Self::PartTwo {
+        let mut completion_scores: Vec<usize> = lines
+            .iter()
+            .filter_map(|line| completion_score(line))
+            .collect();
+        completion_scores.sort_unstable();
+        completion_scores[completion_scores.len() / 2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day10::parse(EXAMPLE).unwrap();
+        assert_eq!(Day10::part_one(&input), 26397);
+        assert_eq!(Day10::part_two(&input), 288957);
+    }
+
+    #[test]
+    fn all_corrupted() {
+        let error = Day10::parse("(]\n").err().unwrap();
+        assert_eq!(
+            error.message,
+            "expected a line that isn't corrupted, found none"
+        );
+    }
 }
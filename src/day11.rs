@@ -1,86 +1,106 @@
-use std::collections::HashMap;
+use crate::error::parse_digit;
+use crate::grid::{Grid, Neighbourhood, Point};
+use crate::{ParseError, Solution};
 use std::str::FromStr;
 
 #[derive(Clone)]
-struct EnergyMap {
-    levels: HashMap<(isize, isize), u32>,
+pub struct EnergyMap {
+    levels: Grid<u32>,
 }
 
 impl FromStr for EnergyMap {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut levels: HashMap<(isize, isize), u32> = HashMap::new();
-        for (y, line) in s.lines().enumerate() {
-            for (x, number) in line.chars().enumerate() {
-                let energy = number.to_digit(10).ok_or(())?;
-                levels.insert((x as isize, y as isize), energy);
-            }
-        }
-
-        let energy_map = Self::new(levels);
+        let levels = Grid::parse(s, "an energy map", parse_digit)?;
+        let energy_map = Self { levels };
         Ok(energy_map)
     }
 }
 
-fn neighbours((x, y): (isize, isize)) -> impl Iterator<Item = (isize, isize)> {
-    itertools::iproduct!((-1..=1), (-1..=1))
-        .filter(|shift| shift != &(0, 0))
-        .map(move |(dx, dy)| (x + dx, y + dy))
-}
-
 impl EnergyMap {
-    fn new(levels: HashMap<(isize, isize), u32>) -> Self {
-        Self { levels }
-    }
-
     fn step(&mut self) -> usize {
-        let mut flashers: Vec<(isize, isize)> = vec![];
-        let mut pending: Vec<(isize, isize)> = vec![];
+        let mut flashers: Vec<Point> = vec![];
+        let mut pending: Vec<Point> = vec![];
 
         for (position, level) in self.levels.iter_mut() {
             *level += 1;
             if *level == 10 {
-                pending.push(*position)
+                pending.push(position)
             }
         }
 
         while let Some(flasher) = pending.pop() {
             flashers.push(flasher);
 
-            for neighbour in neighbours(flasher) {
-                if let Some(neighbour_level) = self.levels.get_mut(&neighbour) {
-                    *neighbour_level += 1;
-                    if *neighbour_level == 10 {
-                        pending.push(neighbour);
-                    }
+            let neighbours: Vec<Point> = self
+                .levels
+                .neighbours(flasher, Neighbourhood::All)
+                .collect();
+            for neighbour in neighbours {
+                let neighbour_level = &mut self.levels[neighbour];
+                *neighbour_level += 1;
+                if *neighbour_level == 10 {
+                    pending.push(neighbour);
                 }
             }
         }
 
         let count = flashers.len();
-        self.levels
-            .extend(flashers.into_iter().zip(std::iter::repeat(0)));
+        for flasher in flashers {
+            self.levels[flasher] = 0;
+        }
         count
     }
 }
 
-pub(crate) fn day11() {
-    let input = std::fs::read_to_string("data/day11.txt").unwrap();
-    let energy_map: EnergyMap = input.parse().unwrap();
+const EXAMPLE: &str = "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+";
+
+pub struct Day11;
 
-    let mut flashers = 0;
-    let mut part_one = energy_map.clone();
-    for _ in 0..100 {
-        flashers += part_one.step();
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = EnergyMap;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input.parse()
     }
-    println!("Part one answer is {}", flashers);
 
-    let mut part_two = energy_map;
-    for step in 1.. {
-        if part_two.step() == 100 {
-            println!("Part two answer is {}", step);
-            break;
-        }
+    fn part_one(energy_map: &Self::Input) -> Self::PartOne {
+        let mut energy_map = energy_map.clone();
+        (0..100).map(|_| energy_map.step()).sum()
+    }
+
+    fn part_two(energy_map: &Self::Input) -> Self::PartTwo {
+        let mut energy_map = energy_map.clone();
+        let octopuses = energy_map.levels.len();
+        (1..).find(|_| energy_map.step() == octopuses).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day11::parse(EXAMPLE).unwrap();
+        assert_eq!(Day11::part_one(&input), 1656);
+        assert_eq!(Day11::part_two(&input), 195);
     }
 }
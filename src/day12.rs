@@ -1,3 +1,4 @@
+use crate::{ParseError, Solution};
 use maplit::btreeset;
 use std::collections::{BTreeSet, HashMap};
 use std::str::FromStr;
@@ -33,21 +34,27 @@ impl<'a> Path<'a> {
 }
 
 #[derive(Default)]
-struct CaveMap {
+pub struct CaveMap {
     caves: HashMap<String, Cave>,
 }
 
 impl FromStr for CaveMap {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut cave_map = CaveMap::default();
-        for line in s.lines() {
-            let mut caves = line.split('-');
-            let cave1 = caves.next().ok_or(())?;
-            let cave2 = caves.next().ok_or(())?;
+        for (index, line) in s.lines().enumerate() {
+            let (cave1, cave2) = line.split_once('-').ok_or_else(|| {
+                ParseError::new(format!("expected a tunnel `a-b`, found `{}`", line))
+                    .line_offset(index)
+            })?;
             cave_map.add_tunnel(cave1, cave2);
         }
+        for cave in ["start", "end"] {
+            if !cave_map.caves.contains_key(cave) {
+                return Err(ParseError::new(format!("no tunnels lead to `{}`", cave)));
+            }
+        }
         Ok(cave_map)
     }
 }
@@ -115,10 +122,47 @@ impl CaveMap {
     }
 }
 
-pub(crate) fn day12() {
-    let input = std::fs::read_to_string("data/day12.txt").unwrap();
-    let cave_map: CaveMap = input.parse().unwrap();
+const EXAMPLE: &str = "\
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+";
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = CaveMap;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input.parse()
+    }
+
+    fn part_one(cave_map: &Self::Input) -> Self::PartOne {
+        cave_map.count_routes(false)
+    }
+
+    fn part_two(cave_map: &Self::Input) -> Self::PartTwo {
+        cave_map.count_routes(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Part one answer is {}", cave_map.count_routes(false));
-    println!("Part two answer is {}", cave_map.count_routes(true));
+    #[test]
+    fn example() {
+        let input = Day12::parse(EXAMPLE).unwrap();
+        assert_eq!(Day12::part_one(&input), 10);
+        assert_eq!(Day12::part_two(&input), 36);
+    }
 }
@@ -1,8 +1,10 @@
+use crate::error::{next_word, parse_word};
+use crate::{Answer, ParseError, Solution};
 use std::fmt;
 use std::{collections::HashSet, fmt::Display, str::FromStr};
 
-#[derive(Default)]
-struct DotMap {
+#[derive(Clone, Default)]
+pub struct DotMap {
     dots: HashSet<(usize, usize)>,
     max_x: usize,
     max_y: usize,
@@ -54,6 +56,12 @@ impl Display for DotMap {
     }
 }
 
+impl From<DotMap> for Answer {
+    fn from(dot_map: DotMap) -> Self {
+        Self::Text(dot_map.to_string())
+    }
+}
+
 #[derive(Debug)]
 enum Axis {
     X,
@@ -66,38 +74,113 @@ struct FoldInstruction {
 }
 
 impl FromStr for FoldInstruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut sections = s.split('=');
-        let axis = match sections.next().ok_or(())?.chars().last() {
-            Some('x') => Axis::X,
-            Some('y') => Axis::Y,
-            _ => return Err(()),
+        let bad_instruction = || ParseError::new("expected `fold along x=` or `fold along y=`");
+        let (prefix, value) = s.split_once('=').ok_or_else(bad_instruction)?;
+        let axis = match prefix {
+            "fold along x" => Axis::X,
+            "fold along y" => Axis::Y,
+            _ => return Err(bad_instruction()),
         };
-        let value = sections.next().ok_or(())?.parse().map_err(|_| ())?;
+        let value = parse_word(s, value, "a fold position")?;
         let instruction = Self { axis, value };
         Ok(instruction)
     }
 }
 
-pub(crate) fn day13() {
-    let mut dot_map = DotMap::default();
-    let input = std::fs::read_to_string("data/day13.txt").unwrap();
-    let mut lines = input.lines();
-    for line in lines.by_ref().take_while(|line| !line.is_empty()) {
-        let mut numbers = line.split(',');
-        let x: usize = numbers.next().unwrap().parse().unwrap();
-        let y: usize = numbers.next().unwrap().parse().unwrap();
-        dot_map.insert((x, y));
+pub struct Manual {
+    dot_map: DotMap,
+    instructions: Vec<FoldInstruction>,
+}
+
+const EXAMPLE: &str = "\
+6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5
+";
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Manual;
+    type PartOne = usize;
+    type PartTwo = DotMap;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut dot_map = DotMap::default();
+        let mut lines = input.lines().enumerate();
+        for (index, line) in lines.by_ref().take_while(|(_, line)| !line.is_empty()) {
+            let mut numbers = line.split(',');
+            let point = next_word(line, &mut numbers, "an x coordinate").and_then(|x| {
+                let y = next_word(line, &mut numbers, "a y coordinate")?;
+                Ok((x, y))
+            });
+            dot_map.insert(point.map_err(|error| error.line_offset(index))?);
+        }
+
+        let instructions: Vec<FoldInstruction> = lines
+            .map(|(index, line)| {
+                line.parse()
+                    .map_err(|error: ParseError| error.line_offset(index))
+            })
+            .collect::<Result<_, _>>()?;
+        if instructions.is_empty() {
+            return Err(ParseError::new("expected fold instructions after the dots"));
+        }
+
+        let manual = Manual {
+            dot_map,
+            instructions,
+        };
+        Ok(manual)
+    }
+
+    fn part_one(manual: &Self::Input) -> Self::PartOne {
+        manual.dot_map.folded(&manual.instructions[0]).dots.len()
     }
-    let instructions: Vec<FoldInstruction> = lines.map(|line| line.parse().unwrap()).collect();
 
-    let part_one = dot_map.folded(&instructions[0]);
-    println!("Part one answer is {}", part_one.dots.len());
+    fn part_two(manual: &Self::Input) -> Self::PartTwo {
+        manual
+            .instructions
+            .iter()
+            .fold(manual.dot_map.clone(), |map, instruction| {
+                map.folded(instruction)
+            })
+    }
+}
 
-    let part_two = instructions
-        .iter()
-        .fold(dot_map, |map, instruction| map.folded(instruction));
-    println!("Part two answer is\n{}", part_two);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day13::parse(EXAMPLE).unwrap();
+        assert_eq!(Day13::part_one(&input), 17);
+        let picture = "#####\n#   #\n#   #\n#   #\n#####\n";
+        assert_eq!(Day13::part_two(&input).to_string(), picture);
+    }
 }
@@ -1,3 +1,4 @@
+use crate::{ParseError, Solution};
 use std::{collections::HashMap, str::FromStr};
 
 use itertools::Itertools;
@@ -54,11 +55,12 @@ impl PolymerTemplate {
 }
 
 impl FromStr for PolymerTemplate {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let start = s.chars().next().ok_or(())?;
-        let end = s.chars().last().ok_or(())?;
+        let empty = || ParseError::new("expected a polymer template, found empty line");
+        let start = s.chars().next().ok_or_else(empty)?;
+        let end = s.chars().last().ok_or_else(empty)?;
         let mut pairs: HashMap<(char, char), usize> = HashMap::new();
         for pair in s.chars().tuple_windows() {
             let count = pairs.entry(pair).or_insert(0);
@@ -69,37 +71,100 @@ impl FromStr for PolymerTemplate {
     }
 }
 
-pub(crate) fn day14() {
-    let input = std::fs::read_to_string("data/day14.txt").unwrap();
-    let mut lines = input.lines();
-    let template: PolymerTemplate = lines.next().unwrap().parse().unwrap();
-
-    let _blank = lines.next().unwrap();
-
-    let mut rules: HashMap<(char, char), char> = HashMap::new();
-    for line in lines {
-        let mut words = line.split_whitespace();
-        let pair = words
-            .next()
-            .unwrap()
-            .chars()
-            .tuple_windows()
-            .next()
-            .unwrap();
-        let _arrow = words.next().unwrap();
-        let out = words.next().unwrap().chars().next().unwrap();
-        rules.insert(pair, out);
+fn parse_rule(line: &str) -> Result<((char, char), char), ParseError> {
+    let bad_rule = || ParseError::new(format!("expected a rule `AB -> C`, found `{}`", line));
+    let (pair, out) = line.split_once(" -> ").ok_or_else(bad_rule)?;
+    let pair = pair.chars().collect_tuple().ok_or_else(bad_rule)?;
+    let out = out.chars().exactly_one().map_err(|_| bad_rule())?;
+    Ok((pair, out))
+}
+
+pub struct Manual {
+    template: PolymerTemplate,
+    rules: HashMap<(char, char), char>,
+}
+
+impl Manual {
+    fn score_after(&self, steps: usize) -> usize {
+        let polymer = (0..steps).fold(self.template.clone(), |template, _| {
+            template.apply_rules(&self.rules)
+        });
+        let counts = polymer.count_chars();
+        let max_count = counts.values().max().unwrap();
+        let min_count = counts.values().min().unwrap();
+        max_count - min_count
+    }
+}
+
+const EXAMPLE: &str = "\
+NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+";
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Manual;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut lines = input.lines().enumerate();
+        let first = lines.next().map_or("", |(_, line)| line);
+        let template: PolymerTemplate = first
+            .parse()
+            .map_err(|error: ParseError| error.line_offset(0))?;
+
+        if let Some((index, line)) = lines.next() {
+            if !line.is_empty() {
+                return Err(ParseError::new("expected a blank line").line_offset(index));
+            }
+        }
+
+        let rules: HashMap<(char, char), char> = lines
+            .map(|(index, line)| parse_rule(line).map_err(|error| error.line_offset(index)))
+            .collect::<Result<_, _>>()?;
+
+        let manual = Manual { template, rules };
+        Ok(manual)
+    }
+
+    fn part_one(manual: &Self::Input) -> Self::PartOne {
+        manual.score_after(10)
+    }
+
+    fn part_two(manual: &Self::Input) -> Self::PartTwo {
+        manual.score_after(40)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let part_one = (0..10).fold(template.clone(), |template, _| template.apply_rules(&rules));
-    let counts = part_one.count_chars();
-    let max_count = counts.values().max().unwrap();
-    let min_count = counts.values().min().unwrap();
-    println!("Part one answer is {}", max_count - min_count);
-
-    let part_two = (0..40).fold(template, |template, _| template.apply_rules(&rules));
-    let counts = part_two.count_chars();
-    let max_count = counts.values().max().unwrap();
-    let min_count = counts.values().min().unwrap();
-    println!("Part two answer is {}", max_count - min_count);
+    #[test]
+    fn example() {
+        let input = Day14::parse(EXAMPLE).unwrap();
+        assert_eq!(Day14::part_one(&input), 1588);
+        assert_eq!(Day14::part_two(&input), 2188189693529);
+    }
 }
@@ -1,144 +1,135 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use crate::error::parse_digit;
+use crate::grid::{Grid, Neighbourhood, Point};
+use crate::search::{self, Problem};
+use crate::{ParseError, Solution};
 use std::str::FromStr;
 
 #[derive(Clone)]
-struct RiskMap {
-    levels: Vec<Vec<u32>>,
-    rows: usize,
-    columns: usize,
+pub struct RiskMap {
+    levels: Grid<u32>,
 }
 
 impl FromStr for RiskMap {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut levels = vec![];
-        for line in s.lines() {
-            let row: Vec<u32> = line
-                .chars()
-                .map(|c| c.to_digit(10).ok_or(()))
-                .collect::<Result<_, _>>()?;
-            levels.push(row);
-        }
-
-        let risk_map = Self::new(levels);
+        let levels = Grid::parse(s, "a risk map", |c| match parse_digit(c)? {
+            0 => Err(ParseError::new(
+                "expected a risk level from 1 to 9, found `0`",
+            )),
+            level => Ok(level),
+        })?;
+        let risk_map = Self { levels };
         Ok(risk_map)
     }
 }
 
-#[derive(PartialEq, Eq)]
-struct SearchState {
-    position: (usize, usize),
-    total_risk: u32,
-}
-
-impl SearchState {
-    fn new(position: (usize, usize), risk: u32) -> Self {
-        Self {
-            position,
-            total_risk: risk,
-        }
+impl RiskMap {
+    fn minimum_risk(&self) -> u32 {
+        search::astar(self).unwrap().cost
     }
-}
 
-impl Ord for SearchState {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.total_risk.cmp(&self.total_risk)
+    fn extended(&self) -> Self {
+        let (columns, rows) = (self.levels.width(), self.levels.height());
+        let levels = Grid::from_fn(5 * columns, 5 * rows, |(x, y)| {
+            let increment = (x / columns) + (y / rows);
+            let mut new_risk = self.levels[(x % columns, y % rows)] + increment as u32;
+            if new_risk > 9 {
+                new_risk %= 9;
+            }
+            new_risk
+        });
+        Self { levels }
     }
 }
 
-impl PartialOrd for SearchState {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+// Travel from the top left to the bottom right.
+impl Problem for RiskMap {
+    type State = Point;
+    type Cost = u32;
+
+    fn start(&self) -> Self::State {
+        (0, 0)
     }
-}
 
-impl RiskMap {
-    fn new(levels: Vec<Vec<u32>>) -> Self {
-        let rows = levels.len();
-        let columns = levels[0].len();
-        Self {
-            levels,
-            rows,
-            columns,
-        }
+    fn neighbours(&self, &position: &Self::State, neighbours: &mut Vec<(Self::State, Self::Cost)>) {
+        let steps = self.levels.neighbours(position, Neighbourhood::Orthogonal);
+        neighbours.extend(steps.map(|neighbour| (neighbour, self.levels[neighbour])));
     }
 
-    fn neighbours(&self, (x, y): (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
-        [(-1, 0), (0, -1), (0, 1), (1, 0)]
-            .iter()
-            .filter_map(move |(dx, dy)| {
-                let neighbour = (x as isize + dx, y as isize + dy);
-                if neighbour.0 < 0 || neighbour.1 < 0 {
-                    return None;
-                }
-                let neighbour = (neighbour.0 as usize, neighbour.1 as usize);
-                if neighbour.0 >= self.rows || neighbour.1 >= self.columns {
-                    return None;
-                }
-                Some(neighbour)
-            })
+    fn is_goal(&self, &(x, y): &Self::State) -> bool {
+        x + 1 == self.levels.width() && y + 1 == self.levels.height()
     }
 
-    fn minimum_risk(&self) -> u32 {
-        let start = (0, 0);
-        let goal = (self.columns - 1, self.rows - 1);
+    // Every step costs at least one.
+    fn heuristic(&self, &(x, y): &Self::State) -> Self::Cost {
+        let distance = (self.levels.width() - 1 - x) + (self.levels.height() - 1 - y);
+        distance as u32
+    }
 
-        let mut risks = vec![vec![std::u32::MAX; self.columns]; self.rows];
-        risks[start.1][start.0] = 0;
+    fn state_count(&self) -> Option<usize> {
+        Some(self.levels.len())
+    }
 
-        let start_state = SearchState::new(start, 0);
-        let mut queue = BinaryHeap::new();
-        queue.push(start_state);
+    fn state_index(&self, &(x, y): &Self::State) -> usize {
+        y * self.levels.width() + x
+    }
 
-        while let Some(state) = queue.pop() {
-            if state.position == goal {
-                break;
-            }
+    fn indexed_state(&self, index: usize) -> Self::State {
+        (index % self.levels.width(), index / self.levels.width())
+    }
+}
 
-            for neighbour in self.neighbours(state.position) {
-                let step_risk = self.levels[neighbour.1][neighbour.0];
-                let total_risk = state.total_risk + step_risk;
-                let best_risk = &mut risks[neighbour.1][neighbour.0];
-                if total_risk >= *best_risk {
-                    continue;
-                }
-                *best_risk = total_risk;
-                let new_state = SearchState::new(neighbour, total_risk);
-                queue.push(new_state);
-            }
-        }
+const EXAMPLE: &str = "\
+1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581
+";
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = RiskMap;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input.parse()
+    }
 
-        risks[goal.1][goal.0]
+    fn part_one(risk_map: &Self::Input) -> Self::PartOne {
+        risk_map.minimum_risk()
     }
 
-    fn extended(&self) -> Self {
-        let mut extended_levels = vec![Vec::with_capacity(5 * self.columns); 5 * self.rows];
-        for (y, extended_row) in extended_levels.iter_mut().enumerate() {
-            for x in 0..5 * self.rows {
-                let orig_x = x % self.columns;
-                let orig_y = y % self.rows;
-                let increment = (x / self.columns) + (y / self.rows);
-                let mut new_risk = self.levels[orig_y][orig_x] + increment as u32;
-                if new_risk > 9 {
-                    new_risk %= 9;
-                }
-                extended_row.push(new_risk);
-            }
-        }
-        Self::new(extended_levels)
+    fn part_two(risk_map: &Self::Input) -> Self::PartTwo {
+        risk_map.extended().minimum_risk()
     }
 }
 
-pub(crate) fn day15() {
-    let input = std::fs::read_to_string("data/day15.txt").unwrap();
-    let risk_map: RiskMap = input.parse().unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let part_one = risk_map.minimum_risk();
-    println!("Part one answer is {}", part_one);
+    #[test]
+    fn example() {
+        let input = Day15::parse(EXAMPLE).unwrap();
+        assert_eq!(Day15::part_one(&input), 40);
+        assert_eq!(Day15::part_two(&input), 315);
+    }
 
-    let risk_map = risk_map.extended();
-    let part_two = risk_map.minimum_risk();
-    println!("Part two answer is {}", part_two);
+    #[test]
+    fn zero_risk() {
+        let error = Day15::parse("19\n10\n").err().unwrap();
+        assert_eq!((error.line, error.column), (Some(2), Some(2)));
+    }
 }
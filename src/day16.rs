@@ -1,3 +1,4 @@
+use crate::{ParseError, Solution};
 use itertools::Itertools;
 use std::convert::TryFrom;
 
@@ -13,6 +14,17 @@ impl From<bitreader::BitReaderError> for PacketError {
     }
 }
 
+impl From<PacketError> for ParseError {
+    fn from(error: PacketError) -> Self {
+        match error {
+            PacketError::BitReaderError(error) => {
+                Self::new(format!("transmission ended mid-packet: {}", error))
+            }
+            PacketError::BadValue => Self::new("malformed packet"),
+        }
+    }
+}
+
 #[derive(Debug)]
 enum PacketType {
     Sum,
@@ -44,7 +56,7 @@ impl TryFrom<u8> for PacketType {
 }
 
 #[derive(Debug)]
-struct Packet {
+pub struct Packet {
     version: u8,
     packet_type: PacketType,
     subpackets: Vec<Packet>,
@@ -130,20 +142,92 @@ fn read_packet(reader: &mut bitreader::BitReader) -> Result<Packet, PacketError>
     Ok(packet)
 }
 
-pub(crate) fn day16() {
-    let input = std::fs::read_to_string("data/day16.txt").unwrap();
-    let transmission: Vec<u8> = input
-        .chars()
-        .tuples()
-        .map(|(a, b)| {
-            let hi = a.to_digit(16).unwrap() as u8;
-            let lo = b.to_digit(16).unwrap() as u8;
-            hi << 4 | lo
-        })
-        .collect();
-    let mut reader = bitreader::BitReader::new(&transmission);
-    let packet = read_packet(&mut reader).unwrap();
-
-    println!("Part one answer is {}", packet.sum_versions());
-    println!("Part two answer is {}", packet.evaluate());
+// The last of the evaluation examples from the puzzle description.
+const EXAMPLE: &str = "\
+9C0141080250320F1802104A08
+";
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Packet;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let line = input.trim_end();
+        let digits: Vec<u8> = line
+            .chars()
+            .enumerate()
+            .map(|(column, c)| {
+                let digit = c.to_digit(16).ok_or_else(|| {
+                    ParseError::new(format!("expected a hexadecimal digit, found `{}`", c))
+                        .column_offset(column)
+                        .line_offset(0)
+                })?;
+                Ok(digit as u8)
+            })
+            .collect::<Result<_, ParseError>>()?;
+        let transmission: Vec<u8> = digits
+            .into_iter()
+            .tuples()
+            .map(|(hi, lo)| hi << 4 | lo)
+            .collect();
+        let mut reader = bitreader::BitReader::new(&transmission);
+        let packet = read_packet(&mut reader)?;
+        Ok(packet)
+    }
+
+    fn part_one(packet: &Self::Input) -> Self::PartOne {
+        packet.sum_versions()
+    }
+
+    fn part_two(packet: &Self::Input) -> Self::PartTwo {
+        packet.evaluate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(part: fn(&Packet) -> u64, examples: &[(&str, u64)]) {
+        for (transmission, expected) in examples {
+            let packet = Day16::parse(transmission).unwrap();
+            assert_eq!(part(&packet), *expected, "{}", transmission);
+        }
+    }
+
+    #[test]
+    fn version_sums() {
+        check(
+            Day16::part_one,
+            &[
+                ("8A004A801A8002F478", 16),
+                ("620080001611562C8802118E34", 12),
+                ("C0015000016115A2E0802F182340", 23),
+                ("A0016C880162017C3686B18A3D4780", 31),
+            ],
+        );
+    }
+
+    #[test]
+    fn evaluation() {
+        check(
+            Day16::part_two,
+            &[
+                ("C200B40A82", 3),
+                ("04005AC33890", 54),
+                ("880086C3E88112", 7),
+                ("CE00C43D881120", 9),
+                ("D8005AC2A8F0", 1),
+                ("F600BC2D8F", 0),
+                ("9C005AC2F8F0", 0),
+                ("9C0141080250320F1802104A08", 1),
+            ],
+        );
+    }
 }
@@ -1,3 +1,165 @@
+use crate::error::{next_word, offset};
+use crate::grid::Grid;
+use crate::{ParseError, Solution};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Target {
+    x_min: isize,
+    x_max: isize,
+    y_min: isize,
+    y_max: isize,
+}
+
+// Read a range such as `x=20..30` from an iterator over slices of `s`.
+fn read_range<'a>(
+    s: &str,
+    ranges: &mut impl Iterator<Item = &'a str>,
+    axis: char,
+) -> Result<(isize, isize), ParseError> {
+    let prefix = format!("{}=", axis);
+    let missing = || ParseError::new(format!("expected `{}` range in target area", prefix));
+    let range = ranges
+        .next()
+        .ok_or_else(|| missing().column_offset(s.len()))?;
+    let bounds = range
+        .strip_prefix(&prefix)
+        .ok_or_else(|| missing().column_offset(offset(s, range)))?;
+
+    let mut bounds = bounds.split("..");
+    let min = next_word(s, &mut bounds, "a lower bound")?;
+    let max = next_word(s, &mut bounds, "an upper bound")?;
+    if min > max {
+        let message = format!("expected `{}` range to be increasing", prefix);
+        return Err(ParseError::new(message).column_offset(offset(s, range)));
+    }
+    Ok((min, max))
+}
+
+impl FromStr for Target {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ranges = s
+            .strip_prefix("target area: ")
+            .ok_or_else(|| ParseError::new("expected `target area: `"))?;
+        let mut ranges = ranges.split(", ");
+        let (x_min, x_max) = read_range(s, &mut ranges, 'x')?;
+        let (y_min, y_max) = read_range(s, &mut ranges, 'y')?;
+
+        let target = Self {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+        };
+        Ok(target)
+    }
+}
+
+/// Every launch that is fast enough upwards hits the target, so there is no highest one.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InfinitelyManyHits;
+
+impl fmt::Display for InfinitelyManyHits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "infinitely many launches hit the target")
+    }
+}
+
+impl std::error::Error for InfinitelyManyHits {}
+
+impl Target {
+    fn contains(&self, (x, y): (isize, isize)) -> bool {
+        self.x_min <= x && x <= self.x_max && self.y_min <= y && y <= self.y_max
+    }
+
+    // Whether a probe at `position`, about to move with `velocity`, can never reach the target.
+    fn out_of_reach(&self, (x, y): (isize, isize), (dx, dy): (isize, isize)) -> bool {
+        let falling_past = y < self.y_min && dy < 0;
+        let left_behind = x < self.x_min && dx <= 0;
+        let right_behind = x > self.x_max && dx >= 0;
+        falling_past || left_behind || right_behind
+    }
+
+    // The velocities worth trying, unless infinitely many launches hit the target.
+    fn search_bounds(
+        &self,
+    ) -> Result<(RangeInclusive<isize>, RangeInclusive<isize>), InfinitelyManyHits> {
+        // Any faster horizontally and we overshoot in the first step.
+        let dx_min = self.x_min.min(0);
+        let dx_max = self.x_max.max(0);
+
+        // If the target includes the launch height then a probe launched upwards always passes
+        // through it again, with ever more steps the faster it was launched. So if a probe can also
+        // stall horizontally in the target, it hits however fast it goes up.
+        let crosses_launch_height = self.y_min <= 0 && 0 <= self.y_max;
+        let stalls_in_target = (dx_min..=dx_max).any(|dx| {
+            let stop = dx.signum() * dx.abs() * (dx.abs() + 1) / 2;
+            self.x_min <= stop && stop <= self.x_max
+        });
+        if crosses_launch_height && stalls_in_target {
+            return Err(InfinitelyManyHits);
+        }
+
+        // Launched upwards at `dy`, a probe is at least `dy` high until it comes back down to the
+        // launch height after `2 * dy + 1` steps, and then immediately falls below `-dy`. So a
+        // faster launch can only hit if the target includes the launch height, and then only if
+        // it's still horizontally in the target after that many steps. But probes that don't
+        // stall in the target have left it after as many steps as their horizontal speed.
+        let mut dy_max = self.y_max.max(-self.y_min - 1);
+        if crosses_launch_height {
+            dy_max = dy_max.max(dx_min.abs()).max(dx_max.abs());
+        }
+
+        // Any faster downwards and we overshoot in the first step.
+        let dy_min = self.y_min.min(0);
+
+        Ok((dx_min..=dx_max, dy_min..=dy_max))
+    }
+
+    /// Every launch that hits the target, unless there are infinitely many.
+    pub fn hits(&self) -> Result<Vec<Hit>, InfinitelyManyHits> {
+        let (dx_range, dy_range) = self.search_bounds()?;
+        let hits = itertools::iproduct!(dx_range, dy_range)
+            .filter_map(|(dx, dy)| Launch::new(dx, dy).fire(self))
+            .collect();
+        Ok(hits)
+    }
+
+    /// Draw a hit as in the puzzle description, with the launch point as `S`, the trajectory as
+    /// `#` and the target as `T`.
+    pub fn plot(&self, hit: &Hit) -> String {
+        let points: HashSet<(isize, isize)> = hit.trajectory.iter().copied().collect();
+        let xs = points.iter().map(|&(x, _)| x);
+        let ys = points.iter().map(|&(_, y)| y);
+        let x_min = xs.clone().chain([self.x_min]).min().unwrap();
+        let x_max = xs.chain([self.x_max]).max().unwrap();
+        let y_min = ys.clone().chain([self.y_min]).min().unwrap();
+        let y_max = ys.chain([self.y_max]).max().unwrap();
+
+        let width = (x_max - x_min + 1) as usize;
+        let height = (y_max - y_min + 1) as usize;
+        let picture = Grid::from_fn(width, height, |(column, row)| {
+            // Rows count down from the top.
+            let position = (x_min + column as isize, y_max - row as isize);
+            if position == (0, 0) {
+                'S'
+            } else if points.contains(&position) {
+                '#'
+            } else if self.contains(position) {
+                'T'
+            } else {
+                '.'
+            }
+        });
+        picture.to_string()
+    }
+}
+
 #[derive(Debug)]
 struct Launch {
     dx: isize,
@@ -13,19 +175,51 @@ impl Launch {
         Trajectory::new(0, 0, self.dx, self.dy)
     }
 
-    fn hits(&self, x_min: isize, x_max: isize, y_min: isize, y_max: isize) -> bool {
-        for (x, y) in self.trajectory() {
-            if y < y_min || x > x_max {
-                return false;
+    // The highest point reached, which may be the launch point.
+    fn apex(&self) -> isize {
+        let dy = self.dy.max(0);
+        (dy * (dy + 1)) / 2
+    }
+
+    // Follow the probe until it either enters the target or can never do so.
+    fn fire(&self, target: &Target) -> Option<Hit> {
+        let mut trajectory = self.trajectory();
+        // The launch point is part of the trajectory, but only positions after a step can hit.
+        let mut points = vec![trajectory.next().unwrap()];
+        loop {
+            let velocity = (trajectory.dx, trajectory.dy);
+            let position = trajectory.next().unwrap();
+            points.push(position);
+            if target.contains(position) {
+                break;
             }
-            if x_min <= x && y <= y_max {
-                return true;
+            if target.out_of_reach(position, velocity) {
+                return None;
             }
         }
-        unreachable!()
+
+        let hit = Hit {
+            velocity: (self.dx, self.dy),
+            step: points.len() - 1,
+            apex: self.apex(),
+            trajectory: points,
+        };
+        Some(hit)
     }
 }
 
+/// A launch that lands in the target.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hit {
+    pub velocity: (isize, isize),
+    /// The step after which the probe is first in the target.
+    pub step: usize,
+    /// The highest point of the whole flight, which may be the launch point.
+    pub apex: isize,
+    /// The probe's positions from the launch point until it is first in the target.
+    pub trajectory: Vec<(isize, isize)>,
+}
+
 #[derive(Debug)]
 struct Trajectory {
     x: isize,
@@ -53,18 +247,113 @@ impl Iterator for Trajectory {
     }
 }
 
-pub(crate) fn day17() {
-    let x_min = 185;
-    let x_max = 221;
-    let y_min = -122;
-    let y_max = -74;
+const EXAMPLE: &str = "target area: x=20..30, y=-10..-5\n";
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Target;
+    type PartOne = Result<isize, InfinitelyManyHits>;
+    type PartTwo = Result<usize, InfinitelyManyHits>;
 
-    // With initial dy > 0: as we hit the ground, dy will again have the initial magnitude but be
-    // negative. We mustn't overshoot in the next step.
-    println!("Part one answer is {}", (121 * 122) / 2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input
+            .trim_end()
+            .parse()
+            .map_err(|error: ParseError| error.line_offset(0))
+    }
+
+    fn part_one(target: &Self::Input) -> Self::PartOne {
+        let hits = target.hits()?;
+        // Launching straight at any point in the target hits it in the first step.
+        let apex = hits.iter().map(|hit| hit.apex).max().unwrap();
+        Ok(apex)
+    }
 
-    let hits = itertools::iproduct!((1..=221), (-122..=121))
-        .filter(|(dx, dy)| Launch::new(*dx, *dy).hits(x_min, x_max, y_min, y_max))
-        .count();
-    println!("Part two answer is {}", hits);
+    fn part_two(target: &Self::Input) -> Self::PartTwo {
+        let hits = target.hits()?;
+        Ok(hits.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day17::parse(EXAMPLE).unwrap();
+        assert_eq!(Day17::part_one(&input), Ok(45));
+        assert_eq!(Day17::part_two(&input), Ok(112));
+    }
+
+    // Count by brute force over a generous range of velocities.
+    fn brute_force(target: &Target) -> (isize, usize) {
+        let launches: Vec<Launch> = itertools::iproduct!(-60..=60, -60..=60)
+            .map(|(dx, dy)| Launch::new(dx, dy))
+            .filter(|launch| launch.fire(target).is_some())
+            .collect();
+        let apex = launches.iter().map(Launch::apex).max().unwrap();
+        (apex, launches.len())
+    }
+
+    #[test]
+    fn any_target_location() {
+        for target in [
+            "target area: x=-30..-20, y=-10..-5",
+            "target area: x=20..30, y=5..10",
+            "target area: x=-30..-20, y=5..10",
+            "target area: x=-5..5, y=-10..-5",
+            "target area: x=-5..5, y=5..10",
+            "target area: x=12..14, y=-3..4",
+            "target area: x=-14..-12, y=0..0",
+        ] {
+            let input = Day17::parse(target).unwrap();
+            let (apex, count) = brute_force(&input);
+            assert_eq!(Day17::part_one(&input), Ok(apex), "{}", target);
+            assert_eq!(Day17::part_two(&input), Ok(count), "{}", target);
+        }
+    }
+
+    #[test]
+    fn plot() {
+        let target = Day17::parse(EXAMPLE).unwrap();
+        let hits = target.hits().unwrap();
+        let hit = hits.iter().find(|hit| hit.velocity == (7, 2)).unwrap();
+        assert_eq!(hit.step, 7);
+        assert_eq!(hit.apex, 3);
+        let expected = "\
+.............#....#............
+.......#..............#........
+...............................
+S........................#.....
+...............................
+...............................
+...........................#...
+...............................
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+....................TTTTTTTT#TT
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+....................TTTTTTTTTTT
+";
+        assert_eq!(target.plot(hit), expected);
+    }
+
+    #[test]
+    fn infinitely_many_hits() {
+        let input = Day17::parse("target area: x=5..7, y=-2..2").unwrap();
+        assert_eq!(Day17::part_one(&input), Err(InfinitelyManyHits));
+        assert_eq!(Day17::part_two(&input), Err(InfinitelyManyHits));
+    }
+
+    #[test]
+    fn parse_error() {
+        let error = Day17::parse("target area: x=20..30, y=-10..x").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(1), Some(31)));
+    }
 }
@@ -1,7 +1,10 @@
+use crate::error::parse_lines;
+use crate::{ParseError, Solution};
 use itertools::Itertools;
+use std::str::FromStr;
 
 #[derive(Clone, Debug)]
-enum SnailfishNumber {
+pub enum SnailfishNumber {
     Regular(u32),
     Pair(Box<(SnailfishNumber, SnailfishNumber)>),
 }
@@ -82,7 +85,7 @@ impl SnailfishNumber {
     fn split(&mut self) -> bool {
         match self {
             Self::Regular(v) if *v >= 10 => {
-                let (v1, v2) = (*v / 2, (*v + 1) / 2);
+                let (v1, v2) = (*v / 2, v.div_ceil(2));
                 *self = Self::Pair(Box::new((Self::Regular(v1), Self::Regular(v2))));
                 true
             }
@@ -92,45 +95,138 @@ impl SnailfishNumber {
     }
 }
 
-fn read_snailfish<T: Iterator<Item = char>>(chars: &mut T) -> SnailfishNumber {
-    let c = chars.next().unwrap();
+fn expect_char<T: Iterator<Item = (usize, char)>>(
+    chars: &mut T,
+    expected: char,
+) -> Result<(), ParseError> {
+    match chars.next() {
+        Some((_, c)) if c == expected => Ok(()),
+        Some((column, c)) => Err(ParseError::new(format!(
+            "expected `{}`, found `{}`",
+            expected, c
+        ))
+        .column_offset(column)),
+        None => Err(ParseError::new(format!(
+            "expected `{}`, found end of line",
+            expected
+        ))),
+    }
+}
+
+// Reads from an iterator of (column, character).
+fn read_snailfish<T: Iterator<Item = (usize, char)>>(
+    chars: &mut T,
+) -> Result<SnailfishNumber, ParseError> {
+    let (column, c) = chars
+        .next()
+        .ok_or_else(|| ParseError::new("expected a snailfish number, found end of line"))?;
     if let Some(d) = c.to_digit(10) {
-        return SnailfishNumber::Regular(d);
+        return Ok(SnailfishNumber::Regular(d));
+    }
+    if c != '[' {
+        let error = ParseError::new(format!("expected `[` or a digit, found `{}`", c));
+        return Err(error.column_offset(column));
     }
 
-    let left = read_snailfish(chars);
-    let _comma = chars.next();
-    let right = read_snailfish(chars);
-    let _close = chars.next();
+    let left = read_snailfish(chars)?;
+    expect_char(chars, ',')?;
+    let right = read_snailfish(chars)?;
+    expect_char(chars, ']')?;
 
-    SnailfishNumber::Pair(Box::new((left, right)))
+    let pair = SnailfishNumber::Pair(Box::new((left, right)));
+    Ok(pair)
 }
 
-pub(crate) fn day18() {
-    let input = std::fs::read_to_string("data/day18.txt").unwrap();
-    let numbers: Vec<SnailfishNumber> = input
-        .lines()
-        .map(|line| read_snailfish(&mut line.chars()))
-        .collect();
-
-    let total = numbers
-        .clone()
-        .into_iter()
-        .reduce(|total, next| total.add(next))
-        .unwrap();
-
-    println!("Part one answer is {}", total.magnitude());
-
-    let max = numbers
-        .into_iter()
-        .permutations(2)
-        .map(|mut pair| {
-            let first = pair.pop().unwrap();
-            let second = pair.pop().unwrap();
-            first.add(second).magnitude()
-        })
-        .max()
-        .unwrap();
-
-    println!("Part two answer is {}", max);
+impl FromStr for SnailfishNumber {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.char_indices();
+        let number = read_snailfish(&mut chars)?;
+        if let Some((column, c)) = chars.next() {
+            let error = ParseError::new(format!("unexpected `{}` after snailfish number", c));
+            return Err(error.column_offset(column));
+        }
+        Ok(number)
+    }
+}
+
+const EXAMPLE: &str = "\
+[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]
+";
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<SnailfishNumber>;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let numbers: Vec<SnailfishNumber> = parse_lines(input, str::parse)?;
+        // Part two adds pairs of different numbers.
+        if numbers.len() < 2 {
+            return Err(ParseError::new(format!(
+                "expected at least 2 snailfish numbers, found {}",
+                numbers.len()
+            )));
+        }
+        Ok(numbers)
+    }
+
+    fn part_one(numbers: &Self::Input) -> Self::PartOne {
+        let total = numbers
+            .iter()
+            .cloned()
+            .reduce(|total, next| total.add(next))
+            .unwrap();
+        total.magnitude()
+    }
+
+    fn part_two(numbers: &Self::Input) -> Self::PartTwo {
+        numbers
+            .iter()
+            .cloned()
+            .permutations(2)
+            .map(|mut pair| {
+                let first = pair.pop().unwrap();
+                let second = pair.pop().unwrap();
+                first.add(second).magnitude()
+            })
+            .max()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day18::parse(EXAMPLE).unwrap();
+        assert_eq!(Day18::part_one(&input), 4140);
+        assert_eq!(Day18::part_two(&input), 3993);
+    }
+
+    #[test]
+    fn too_few_numbers() {
+        let error = Day18::parse("[1,2]\n").unwrap_err();
+        assert_eq!(
+            error.message,
+            "expected at least 2 snailfish numbers, found 1"
+        );
+    }
 }
@@ -1,5 +1,9 @@
+use crate::error::next_word;
+use crate::{ParseError, Solution};
 use maplit::hashmap;
+use std::cell::OnceCell;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::ops::Sub;
 use std::str::FromStr;
 
@@ -52,7 +56,7 @@ impl Vec3D {
     }
 
     fn normalized(&self) -> Self {
-        let mut coords = vec![self.x.abs(), self.y.abs(), self.z.abs()];
+        let mut coords = [self.x.abs(), self.y.abs(), self.z.abs()];
         coords.sort_unstable();
         Self::new(coords[0], coords[1], coords[2])
     }
@@ -66,19 +70,19 @@ impl Sub for Vec3D {
 }
 
 impl FromStr for Vec3D {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut coords = s.split(',');
-        let x = coords.next().ok_or(())?.parse().map_err(|_| ())?;
-        let y = coords.next().ok_or(())?.parse().map_err(|_| ())?;
-        let z = coords.next().ok_or(())?.parse().map_err(|_| ())?;
+        let x = next_word(s, &mut coords, "an x coordinate")?;
+        let y = next_word(s, &mut coords, "a y coordinate")?;
+        let z = next_word(s, &mut coords, "a z coordinate")?;
         Ok(Self::new(x, y, z))
     }
 }
 
 #[derive(Clone, Debug)]
-struct Scanner {
+pub struct Scanner {
     readings: HashSet<Vec3D>,
     fingerprint: HashMap<Vec3D, Vec<Vec3D>>,
 }
@@ -88,8 +92,8 @@ impl Scanner {
         let mut fingerprint: HashMap<Vec3D, Vec<Vec3D>> = HashMap::default();
         for pair in readings.iter().cloned().combinations(2) {
             let diff = (pair[0] - pair[1]).normalized();
-            let entry = fingerprint.entry(diff).or_insert_with(Vec::new);
-            entry.extend(pair.into_iter());
+            let entry = fingerprint.entry(diff).or_default();
+            entry.extend(pair);
         }
 
         Self {
@@ -140,33 +144,65 @@ impl Scanner {
     }
 }
 
-fn read_scanner<'a, T: Iterator<Item = &'a str>>(lines: &mut T) -> Option<Scanner> {
-    lines.next()?;
+// Reads from an iterator of (line index, line).
+fn read_scanner<'a, T: Iterator<Item = (usize, &'a str)>>(
+    lines: &mut T,
+) -> Result<Option<Scanner>, ParseError> {
+    let (index, header) = match lines.next() {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    if !header.starts_with("--- scanner ") {
+        let error = ParseError::new(format!("expected `--- scanner N ---`, found `{}`", header));
+        return Err(error.line_offset(index));
+    }
 
     let readings: HashSet<Vec3D> = lines
         .by_ref()
-        .take_while(|line| !line.is_empty())
-        .map(|line| line.parse().unwrap())
-        .collect();
+        .take_while(|(_, line)| !line.is_empty())
+        .map(|(index, line)| {
+            line.parse()
+                .map_err(|error: ParseError| error.line_offset(index))
+        })
+        .collect::<Result<_, _>>()?;
     let scanner = Scanner::new(readings);
 
-    Some(scanner)
+    Ok(Some(scanner))
+}
+
+/// Some scanner shares too few beacons with the others to be lined up with them.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Unaligned {
+    scanner: usize,
 }
 
-pub(crate) fn day19() {
-    let input = std::fs::read_to_string("data/day19.txt").unwrap();
-    let mut lines = input.lines();
-    let mut scanners: Vec<Scanner> = vec![];
-    while let Some(scanner) = read_scanner(&mut lines) {
-        scanners.push(scanner);
+impl fmt::Display for Unaligned {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "scanner {} can't be lined up with any of the others",
+            self.scanner
+        )
     }
+}
+
+impl std::error::Error for Unaligned {}
 
-    let mut unmapped: VecDeque<Scanner> = scanners.iter().cloned().collect();
-    let start = unmapped.pop_front().unwrap();
+// Every beacon, and where each scanner is, all relative to the first scanner.
+struct Map {
+    beacons: HashSet<Vec3D>,
+    scanners: Vec<Vec3D>,
+}
+
+fn assemble(scanners: &[Scanner]) -> Result<Map, Unaligned> {
+    let mut unmapped: VecDeque<(usize, Scanner)> = scanners.iter().cloned().enumerate().collect();
+    let (_, start) = unmapped.pop_front().unwrap();
     let mut full_map: HashSet<Vec3D> = start.readings.clone();
     let mut mapped: HashMap<Vec3D, Scanner> = hashmap! { Vec3D::default() => start };
 
-    while let Some(unfixed) = unmapped.pop_front() {
+    // How many scanners in a row have failed to line up since the map last grew.
+    let mut stalled = 0;
+    while let Some((index, unfixed)) = unmapped.pop_front() {
         let mut realigned = None;
         for fixed in mapped.values() {
             realigned = fixed.find_match(&unfixed);
@@ -177,23 +213,254 @@ pub(crate) fn day19() {
 
         match realigned {
             None => {
-                unmapped.push_back(unfixed);
+                unmapped.push_back((index, unfixed));
+                stalled += 1;
+                if stalled == unmapped.len() {
+                    let scanner = unmapped.iter().map(|(index, _)| *index).min().unwrap();
+                    return Err(Unaligned { scanner });
+                }
             }
             Some((position, alignment)) => {
                 full_map.extend(alignment.iter());
                 mapped.insert(position, Scanner::new(alignment));
+                stalled = 0;
             }
         }
     }
 
-    println!("Part one answer is {}", full_map.len());
+    let scanners = mapped.into_keys().collect();
+    Ok(Map {
+        beacons: full_map,
+        scanners,
+    })
+}
+
+/// The scanner reports, and the map they make once they're lined up.
+pub struct Scanners {
+    scanners: Vec<Scanner>,
+    // Lining the scanners up is the hard part, and both parts need it, so whichever part runs
+    // first does it for both.
+    map: OnceCell<Result<Map, Unaligned>>,
+}
+
+impl Scanners {
+    fn map(&self) -> Result<&Map, Unaligned> {
+        self.map
+            .get_or_init(|| assemble(&self.scanners))
+            .as_ref()
+            .map_err(|error| *error)
+    }
+}
+
+const EXAMPLE: &str = "\
+--- scanner 0 ---
+404,-588,-901
+528,-643,409
+-838,591,734
+390,-675,-793
+-537,-823,-458
+-485,-357,347
+-345,-311,381
+-661,-816,-575
+-876,649,763
+-618,-824,-621
+553,345,-567
+474,580,667
+-447,-329,318
+-584,868,-557
+544,-627,-890
+564,392,-477
+455,729,728
+-892,524,684
+-689,845,-530
+423,-701,434
+7,-33,-71
+630,319,-379
+443,580,662
+-789,900,-551
+459,-707,401
+
+--- scanner 1 ---
+686,422,578
+605,423,415
+515,917,-361
+-336,658,858
+95,138,22
+-476,619,847
+-340,-569,-846
+567,-361,727
+-460,603,-452
+669,-402,600
+729,430,532
+-500,-761,534
+-322,571,750
+-466,-666,-811
+-429,-592,574
+-355,545,-477
+703,-491,-529
+-328,-685,520
+413,935,-424
+-391,539,-444
+586,-435,557
+-364,-763,-893
+807,-499,-711
+755,-354,-619
+553,889,-390
+
+--- scanner 2 ---
+649,640,665
+682,-795,504
+-784,533,-524
+-644,584,-595
+-588,-843,648
+-30,6,44
+-674,560,763
+500,723,-460
+609,671,-379
+-555,-800,653
+-675,-892,-343
+697,-426,-610
+578,704,681
+493,664,-388
+-671,-858,530
+-667,343,800
+571,-461,-707
+-138,-166,112
+-889,563,-600
+646,-828,498
+640,759,510
+-630,509,768
+-681,-892,-333
+673,-379,-804
+-742,-814,-386
+577,-820,562
+
+--- scanner 3 ---
+-589,542,597
+605,-692,669
+-500,565,-823
+-660,373,557
+-458,-679,-417
+-488,449,543
+-626,468,-788
+338,-750,-386
+528,-832,-391
+562,-778,733
+-938,-730,414
+543,643,-506
+-524,371,-870
+407,773,750
+-104,29,83
+378,-903,-323
+-778,-728,485
+426,699,580
+-438,-605,-362
+-469,-447,-387
+509,732,623
+647,635,-688
+-868,-804,481
+614,-800,639
+595,780,-596
+
+--- scanner 4 ---
+727,592,562
+-293,-554,779
+441,611,-461
+-714,465,-776
+-743,427,-804
+-660,-479,-426
+832,-632,460
+927,-485,-438
+408,393,-506
+466,436,-512
+110,16,151
+-258,-428,682
+-393,719,612
+-211,-452,876
+808,-476,-593
+-575,615,604
+-485,667,467
+-680,325,-822
+-627,-443,-432
+872,-547,-609
+833,512,582
+807,604,487
+839,-516,451
+891,-625,532
+-652,-548,-490
+30,-46,-14
+";
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Scanners;
+    type PartOne = Result<usize, Unaligned>;
+    type PartTwo = Result<isize, Unaligned>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut lines = input.lines().enumerate();
+        let mut scanners: Vec<Scanner> = vec![];
+        while let Some(scanner) = read_scanner(&mut lines)? {
+            scanners.push(scanner);
+        }
+        // Part two measures between pairs of different scanners.
+        if scanners.len() < 2 {
+            return Err(ParseError::new(format!(
+                "expected at least 2 scanner reports, found {}",
+                scanners.len()
+            )));
+        }
+        let map = OnceCell::new();
+        Ok(Scanners { scanners, map })
+    }
+
+    fn part_one(scanners: &Self::Input) -> Self::PartOne {
+        let map = scanners.map()?;
+        Ok(map.beacons.len())
+    }
 
-    let part_two = mapped
-        .keys()
-        .combinations(2)
-        .map(|points| points[0].distance(points[1]))
-        .max()
-        .unwrap();
+    fn part_two(scanners: &Self::Input) -> Self::PartTwo {
+        let map = scanners.map()?;
+        let furthest = map
+            .scanners
+            .iter()
+            .combinations(2)
+            .map(|points| points[0].distance(points[1]))
+            .max()
+            .unwrap();
+        Ok(furthest)
+    }
+}
 
-    println!("Part two answer is {}", part_two);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day19::parse(EXAMPLE).unwrap();
+        assert_eq!(Day19::part_one(&input), Ok(79));
+        assert_eq!(Day19::part_two(&input), Ok(3621));
+    }
+
+    #[test]
+    fn one_scanner() {
+        let error = Day19::parse("--- scanner 0 ---\n1,2,3\n").err().unwrap();
+        assert_eq!(
+            error.message,
+            "expected at least 2 scanner reports, found 1"
+        );
+    }
+
+    #[test]
+    fn unaligned() {
+        let input = format!("{}\n--- scanner 5 ---\n1,2,3\n4,5,6\n", EXAMPLE);
+        let input = Day19::parse(&input).unwrap();
+        assert_eq!(Day19::part_one(&input), Err(Unaligned { scanner: 5 }));
+        assert_eq!(Day19::part_two(&input), Err(Unaligned { scanner: 5 }));
+    }
 }
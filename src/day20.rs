@@ -1,6 +1,5 @@
-use std::collections::HashMap;
-
-type Point = (isize, isize);
+use crate::grid::{Grid, Point};
+use crate::{ParseError, Solution};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum Pixel {
@@ -8,6 +7,31 @@ enum Pixel {
     Light,
 }
 
+impl TryFrom<char> for Pixel {
+    type Error = ParseError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        let pixel = match c {
+            '.' => Self::Dark,
+            '#' => Self::Light,
+            _ => {
+                return Err(ParseError::new(format!(
+                    "expected `.` or `#`, found `{}`",
+                    c
+                )))
+            }
+        };
+        Ok(pixel)
+    }
+}
+
+// Parse a line of pixels.
+fn read_pixels(line: &str) -> impl Iterator<Item = Result<Pixel, ParseError>> + '_ {
+    line.chars()
+        .enumerate()
+        .map(|(x, c)| Pixel::try_from(c).map_err(|error| error.column_offset(x)))
+}
+
 impl Pixel {
     fn as_bit(&self) -> usize {
         match self {
@@ -19,72 +43,51 @@ impl Pixel {
 
 #[derive(Clone, Debug)]
 struct Image {
+    // Every pixel outside the grid is the same.
     background: Pixel,
-    pixels: HashMap<Point, Pixel>,
-
-    // The extent of the image that is not simply background.
-    min_x: isize,
-    max_x: isize,
-    min_y: isize,
-    max_y: isize,
+    pixels: Grid<Pixel>,
 }
 
 impl Image {
-    fn new(pixels: HashMap<Point, Pixel>) -> Self {
-        let min_x = pixels.keys().map(|point| point.0).min().unwrap_or(0);
-        let min_y = pixels.keys().map(|point| point.1).min().unwrap_or(0);
-        let max_x = pixels.keys().map(|point| point.0).max().unwrap_or(0);
-        let max_y = pixels.keys().map(|point| point.1).max().unwrap_or(0);
+    fn new(pixels: Grid<Pixel>) -> Self {
         let background = Pixel::Dark;
-        Self {
-            background,
-            pixels,
-            min_x,
-            max_x,
-            min_y,
-            max_y,
-        }
+        Self { background, pixels }
     }
 
-    fn get_pixel(&self, point: &Point) -> Pixel {
-        *self.pixels.get(point).unwrap_or(&self.background)
+    // The pixel at `point` after shifting the image by `shift` in each direction.
+    fn get_pixel(&self, (x, y): Point, shift: usize) -> Pixel {
+        let point = x.checked_sub(shift).zip(y.checked_sub(shift));
+        point
+            .and_then(|point| self.pixels.get(point))
+            .copied()
+            .unwrap_or(self.background)
     }
 
+    // The enhanced image grows by one pixel in each direction.
     fn enhance(&mut self, key: &[Pixel]) {
-        let mut pixels: HashMap<Point, Pixel> = HashMap::default();
-        for (x, y) in itertools::iproduct!(
-            self.min_x - 1..=self.max_x + 1,
-            self.min_y - 1..=self.max_y + 1
-        ) {
-            let pixel = self.enhanced_at(&(x, y), key);
-            pixels.insert((x, y), pixel);
-        }
-
+        let width = self.pixels.width() + 2;
+        let height = self.pixels.height() + 2;
+        let pixels = Grid::from_fn(width, height, |point| self.enhanced_at(point, key));
         let background = match self.background {
             Pixel::Dark => key[0],
             Pixel::Light => key[511],
         };
 
         self.pixels = pixels;
-        self.min_x -= 1;
-        self.max_x += 1;
-        self.min_y -= 1;
-        self.max_y += 1;
         self.background = background;
     }
 
-    fn enhanced_at(&self, point: &Point, key: &[Pixel]) -> Pixel {
-        let &(x, y) = point;
+    // The enhanced pixel at `point` of the grown image.
+    fn enhanced_at(&self, (x, y): Point, key: &[Pixel]) -> Pixel {
         let mut number = 0;
-        for yi in [y - 1, y, y + 1] {
-            for xi in [x - 1, x, x + 1] {
-                let pixel = self.get_pixel(&(xi, yi));
+        for yi in [y, y + 1, y + 2] {
+            for xi in [x, x + 1, x + 2] {
+                let pixel = self.get_pixel((xi, yi), 2);
                 let bit = pixel.as_bit();
                 number *= 2;
                 number += bit;
             }
         }
-
         key[number]
     }
 
@@ -97,36 +100,82 @@ impl Image {
     }
 }
 
-pub(crate) fn day20() {
-    let input = std::fs::read_to_string("data/day20.txt").unwrap();
-    let mut lines = input.lines();
+pub struct Puzzle {
+    key: Vec<Pixel>,
+    image: Image,
+}
 
-    let key_line = lines.next().unwrap();
-    let key: Vec<Pixel> = key_line
-        .chars()
-        .map(|c| if c == '.' { Pixel::Dark } else { Pixel::Light })
-        .collect();
+impl Puzzle {
+    fn lit_after(&self, steps: usize) -> usize {
+        let mut image = self.image.clone();
+        for _ in 0..steps {
+            image.enhance(&self.key);
+        }
+        image.count_lit()
+    }
+}
 
-    let _blank = lines.next().unwrap();
+const EXAMPLE: &str = "\
+..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+#..#.
+#....
+##..#
+..#..
+..###
+";
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Puzzle;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut sections = input.splitn(3, '\n');
+        let key_line = sections.next().unwrap_or_default().trim_end_matches('\r');
+        let key: Vec<Pixel> = read_pixels(key_line)
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.line_offset(0))?;
+        if key.len() != 512 {
+            let error = ParseError::new(format!("expected 512 pixels, found {}", key.len()));
+            return Err(error.line_offset(0));
+        }
 
-    let mut pixels: HashMap<Point, Pixel> = HashMap::default();
-    for (y, line) in lines.enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pixel = if c == '.' { Pixel::Dark } else { Pixel::Light };
-            pixels.insert((x as isize, y as isize), pixel);
+        if sections.next().map(|line| line.trim_end_matches('\r')) != Some("") {
+            return Err(ParseError::new("expected a blank line").line_offset(1));
         }
+
+        let image_lines = sections.next().unwrap_or_default();
+        let pixels = Grid::parse(image_lines, "an image", Pixel::try_from)
+            .map_err(|error| error.line_offset(2))?;
+        let image = Image::new(pixels);
+
+        let puzzle = Puzzle { key, image };
+        Ok(puzzle)
+    }
+
+    fn part_one(puzzle: &Self::Input) -> Self::PartOne {
+        puzzle.lit_after(2)
     }
-    let image = Image::new(pixels);
 
-    let mut part_one = image.clone();
-    for _ in 0..2 {
-        part_one.enhance(&key);
+    fn part_two(puzzle: &Self::Input) -> Self::PartTwo {
+        puzzle.lit_after(50)
     }
-    println!("Part one answer is {}", part_one.count_lit());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut part_two = image;
-    for _ in 0..50 {
-        part_two.enhance(&key);
+    #[test]
+    fn example() {
+        let input = Day20::parse(EXAMPLE).unwrap();
+        assert_eq!(Day20::part_one(&input), 35);
+        assert_eq!(Day20::part_two(&input), 3351);
     }
-    println!("Part two answer is {}", part_two.count_lit());
 }
@@ -1,91 +1,648 @@
+use crate::error::{expect_word, next_word, offset, parse_lines};
+use crate::{ParseError, Solution};
 use maplit::hashmap;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::num::NonZeroUsize;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct Player {
+pub struct Player {
     position: usize,
     score: usize,
 }
 
 impl Player {
-    fn new(position: usize) -> Self {
+    /// A player with no points yet, starting on a space numbered from one.
+    ///
+    /// The space must be on the board the game is played on.
+    pub fn new(position: usize) -> Self {
         Self { position, score: 0 }
     }
 
-    fn roll(&mut self, value: usize) {
-        self.position += value;
-        self.position %= 10;
-        if self.position == 0 {
-            self.position = 10
-        };
+    fn roll(&mut self, value: usize, board_size: usize) {
+        self.position = (self.position + value - 1) % board_size + 1;
         self.score += self.position;
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct GameState {
-    player1: Player,
-    player2: Player,
+/// The rules of a game of Dirac Dice.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Rules {
+    board_size: usize,
+    winning_score: usize,
+    die_faces: usize,
+    rolls_per_turn: usize,
 }
 
-impl GameState {
-    fn new(player1: Player, player2: Player) -> Self {
-        Self { player1, player2 }
+/// Rules that no game can be played by.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InvalidRules {
+    EmptyBoard,
+    FacelessDie,
+}
+
+impl fmt::Display for InvalidRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyBoard => write!(f, "the board must have at least one space"),
+            Self::FacelessDie => write!(f, "the die must have at least one face"),
+        }
     }
+}
+
+impl std::error::Error for InvalidRules {}
 
-    fn roll(&mut self, value: usize, player1_turn: bool) {
-        let player = if player1_turn {
-            &mut self.player1
-        } else {
-            &mut self.player2
+impl Rules {
+    pub fn new(
+        board_size: usize,
+        winning_score: usize,
+        die_faces: usize,
+        rolls_per_turn: usize,
+    ) -> Result<Self, InvalidRules> {
+        if board_size == 0 {
+            return Err(InvalidRules::EmptyBoard);
+        }
+        if die_faces == 0 {
+            return Err(InvalidRules::FacelessDie);
+        }
+        let rules = Self {
+            board_size,
+            winning_score,
+            die_faces,
+            rolls_per_turn,
         };
-        player.roll(value);
+        Ok(rules)
     }
-}
 
-pub(crate) fn day21() {
-    let mut player1 = Player::new(8);
-    let mut player2 = Player::new(2);
-    let mut dice = 1;
-    'outer: loop {
-        for player in [&mut player1, &mut player2] {
-            player.roll(dice + dice + 1 + dice + 2);
-            dice += 3;
-            if player.score >= 1000 {
-                break 'outer;
+    /// The practice game, played with a deterministic die.
+    pub const PRACTICE: Self = Self {
+        board_size: 10,
+        winning_score: 1000,
+        die_faces: 100,
+        rolls_per_turn: 3,
+    };
+
+    /// The real game, played with the Dirac die.
+    pub const DIRAC: Self = Self {
+        board_size: 10,
+        winning_score: 21,
+        die_faces: 3,
+        rolls_per_turn: 3,
+    };
+
+    // Each possible total of one turn's rolls of the Dirac die, and how many ways it can come up.
+    fn roll_distribution(&self) -> Vec<(usize, u128)> {
+        let mut ways = vec![1u128];
+        for _ in 0..self.rolls_per_turn {
+            let mut next = vec![0; ways.len() + self.die_faces];
+            for (total, count) in ways.iter().enumerate() {
+                for face in 1..=self.die_faces {
+                    next[total + face] += count;
+                }
             }
+            ways = next;
         }
+        ways.into_iter()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .collect()
     }
-    let loser_score = player1.score.min(player2.score);
-    println!("Part one answer is {}", (dice - 1) * loser_score);
+}
 
-    let player1 = Player::new(8);
-    let player2 = Player::new(2);
-    let mut player1_wins = 0;
-    let mut player2_wins = 0;
+/// The outcome of a practice game.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Practice {
+    /// The index of the winning player.
+    pub winner: usize,
+    pub scores: Vec<usize>,
+    pub rolls: usize,
+}
 
-    let game = GameState::new(player1, player2);
-    let mut worlds = hashmap! { game => 1u64 };
+impl Practice {
+    /// How many games each player won, which is one for the winner and none for everyone else.
+    pub fn wins(&self) -> Vec<u64> {
+        (0..self.scores.len())
+            .map(|player| u64::from(player == self.winner))
+            .collect()
+    }
+
+    /// The lowest losing score multiplied by the number of rolls.
+    pub fn result(&self) -> usize {
+        let loser_score = self
+            .scores
+            .iter()
+            .enumerate()
+            .filter(|&(player, _)| player != self.winner)
+            .map(|(_, &score)| score)
+            .min()
+            .unwrap_or(0);
+        self.rolls * loser_score
+    }
+}
+
+/// The numbers of universes, or the fractions describing them, got too large to work with exactly.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "too many universes to count exactly")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// Play with a deterministic die.
+///
+/// There must be at least one player.
+pub fn play_practice(players: &[Player], rules: &Rules) -> Practice {
+    let mut players = players.to_vec();
+    let mut rolls = 0;
+    for turn in 0.. {
+        let value: usize = (rolls..rolls + rules.rolls_per_turn)
+            .map(|roll| roll % rules.die_faces + 1)
+            .sum();
+        rolls += rules.rolls_per_turn;
+        let current = turn % players.len();
+        let player = &mut players[current];
+        player.roll(value, rules.board_size);
+        if player.score >= rules.winning_score {
+            let scores = players.iter().map(|player| player.score).collect();
+            return Practice {
+                winner: current,
+                scores,
+                rolls,
+            };
+        }
+    }
+    unreachable!()
+}
+
+fn multiply(a: u128, b: u128) -> Result<u128, Overflow> {
+    a.checked_mul(b).ok_or(Overflow)
+}
+
+fn add_to(total: &mut u128, value: u128) -> Result<(), Overflow> {
+    *total = total.checked_add(value).ok_or(Overflow)?;
+    Ok(())
+}
+
+// How one player fares after some number of their own turns with the Dirac die: how many
+// universes they have just won in, and how many they are still playing in, with the total of their
+// scores across each.
+#[derive(Clone, Debug, Default)]
+struct Progress {
+    won: u128,
+    won_scores: u128,
+    playing: u128,
+    playing_scores: u128,
+}
+
+// A player's progress after each of their turns, starting with none, until they have won in
+// every universe.
+fn play_alone(player: Player, rules: &Rules) -> Result<Vec<Progress>, Overflow> {
+    let distribution = rules.roll_distribution();
+    let mut worlds = hashmap! { player => 1u128 };
+    let start = Progress {
+        playing: 1,
+        playing_scores: player.score as u128,
+        ..Progress::default()
+    };
+    let mut progress = vec![start];
 
-    let mut player1_turn = true;
     while !worlds.is_empty() {
+        let mut turn = Progress::default();
         let mut new_worlds = HashMap::default();
-        for (state, count) in &worlds {
-            for (roll, ways) in [(3, 1u64), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)] {
-                let new_count = ways * count;
-                let mut new_state = *state;
-                new_state.roll(roll, player1_turn);
-                if new_state.player1.score >= 21 {
-                    player1_wins += new_count;
-                } else if new_state.player2.score >= 21 {
-                    player2_wins += new_count;
+        for (player, count) in &worlds {
+            for &(roll, ways) in &distribution {
+                let new_count = multiply(ways, *count)?;
+                let mut player = *player;
+                player.roll(roll, rules.board_size);
+                let scores = multiply(new_count, player.score as u128)?;
+                if player.score >= rules.winning_score {
+                    add_to(&mut turn.won, new_count)?;
+                    add_to(&mut turn.won_scores, scores)?;
                 } else {
-                    *new_worlds.entry(new_state).or_default() += new_count;
+                    add_to(&mut turn.playing, new_count)?;
+                    add_to(&mut turn.playing_scores, scores)?;
+                    add_to(new_worlds.entry(player).or_default(), new_count)?;
                 }
             }
         }
-        player1_turn = !player1_turn;
+        progress.push(turn);
         worlds = new_worlds;
     }
-    println!("Part two answer is {}", player1_wins.max(player2_wins));
+    Ok(progress)
+}
+
+// The universes of the Dirac game that end after a given number of turns.
+struct Ending {
+    turns: usize,
+    winner: usize,
+    universes: u128,
+    // The total of each player's final scores across those universes.
+    scores: Vec<u128>,
+}
+
+// Play out every universe of the Dirac game, returning how they end.
+//
+// No player's rolls depend on anyone else's, so each player is played alone and the numbers of
+// universes are multiplied together. Tracking everyone at once would give far more states.
+fn play_universes(players: &[Player], rules: &Rules) -> Result<Vec<Ending>, Overflow> {
+    let progress = players
+        .iter()
+        .map(|&player| play_alone(player, rules))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut endings = vec![];
+    for turns in 1.. {
+        let winner = (turns - 1) % players.len();
+        // Where each player is, given how many turns they have had.
+        let now: Vec<&Progress> = progress
+            .iter()
+            .enumerate()
+            .map(|(index, progress)| &progress[(turns + players.len() - 1 - index) / players.len()])
+            .collect();
+        // The number of ways for everyone but `skipped` to be still playing.
+        let playing = |skipped: &[usize]| {
+            now.iter()
+                .enumerate()
+                .filter(|(index, _)| !skipped.contains(index))
+                .try_fold(1, |product, (_, progress)| {
+                    multiply(product, progress.playing)
+                })
+        };
+
+        let still_playing = playing(&[winner])?;
+        let universes = multiply(now[winner].won, still_playing)?;
+        if universes > 0 {
+            let scores = (0..players.len())
+                .map(|index| {
+                    if index == winner {
+                        multiply(now[winner].won_scores, still_playing)
+                    } else {
+                        let rest = multiply(now[winner].won, playing(&[winner, index])?)?;
+                        multiply(now[index].playing_scores, rest)
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+            endings.push(Ending {
+                turns,
+                winner,
+                universes,
+                scores,
+            });
+        }
+
+        if multiply(now[winner].playing, still_playing)? == 0 {
+            break;
+        }
+    }
+    Ok(endings)
+}
+
+/// Play with the Dirac die, returning how many universes each player wins in.
+pub fn play_dirac(players: &[Player], rules: &Rules) -> Result<Vec<u128>, Overflow> {
+    let mut wins = vec![0; players.len()];
+    for ending in play_universes(players, rules)? {
+        add_to(&mut wins[ending.winner], ending.universes)?;
+    }
+    Ok(wins)
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// A non-negative fraction, always in its lowest terms.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Fraction {
+    pub numerator: u128,
+    pub denominator: u128,
+}
+
+impl Fraction {
+    pub fn new(numerator: u128, denominator: u128) -> Self {
+        let divisor = gcd(numerator, denominator).max(1);
+        Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// The sum of two fractions, or `None` if it doesn't fit.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let divisor = gcd(self.denominator, other.denominator);
+        let left = self.numerator.checked_mul(other.denominator / divisor)?;
+        let right = other.numerator.checked_mul(self.denominator / divisor)?;
+        let denominator = (self.denominator / divisor).checked_mul(other.denominator)?;
+        Some(Self::new(left.checked_add(right)?, denominator))
+    }
+
+    /// This fraction divided by a whole number, or `None` if it doesn't fit.
+    pub fn checked_div(self, divisor: u128) -> Option<Self> {
+        // Cancelling first keeps the denominator as small as possible.
+        let common = gcd(self.numerator, divisor).max(1);
+        let denominator = self.denominator.checked_mul(divisor / common)?;
+        Some(Self::new(self.numerator / common, denominator))
+    }
+}
+
+impl Default for Fraction {
+    fn default() -> Self {
+        Self::new(0, 1)
+    }
+}
+
+impl fmt::Display for Fraction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+/// Exact statistics about a game played with the Dirac die.
+///
+/// Every universe in which a game ends after `n` turns carries probability `1 / k^n`, where `k` is
+/// the number of equally likely outcomes of a turn.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Analysis {
+    pub win_probabilities: Vec<Fraction>,
+    /// The probability of the game ending after each number of turns, counting every player's.
+    pub turns: BTreeMap<usize, Fraction>,
+    /// Each player's expected score when the game ends.
+    pub expected_scores: Vec<Fraction>,
+}
+
+/// Work out the exact odds of a game played with the Dirac die.
+pub fn analyse(players: &[Player], rules: &Rules) -> Result<Analysis, Overflow> {
+    let outcomes = u32::try_from(rules.rolls_per_turn)
+        .ok()
+        .and_then(|rolls| (rules.die_faces as u128).checked_pow(rolls))
+        .ok_or(Overflow)?;
+    // Every universe that ends on the same turn is equally likely, so this is the chance of
+    // `count` of them. Dividing one turn at a time keeps the fraction in its lowest terms, where
+    // `outcomes^turns` alone could be too large.
+    let chance = |count: u128, turns: usize| {
+        (0..turns).try_fold(Fraction::new(count, 1), |fraction, _| {
+            fraction.checked_div(outcomes).ok_or(Overflow)
+        })
+    };
+    let add = |total: &mut Fraction, fraction: Fraction| {
+        *total = total.checked_add(fraction).ok_or(Overflow)?;
+        Ok(())
+    };
+
+    let mut win_probabilities = vec![Fraction::default(); players.len()];
+    let mut turns = BTreeMap::new();
+    let mut expected_scores = vec![Fraction::default(); players.len()];
+    for ending in play_universes(players, rules)? {
+        let probability = chance(ending.universes, ending.turns)?;
+        add(&mut win_probabilities[ending.winner], probability)?;
+        turns.insert(ending.turns, probability);
+        for (expected, scores) in expected_scores.iter_mut().zip(ending.scores) {
+            add(expected, chance(scores, ending.turns)?)?;
+        }
+    }
+
+    let analysis = Analysis {
+        win_probabilities,
+        turns,
+        expected_scores,
+    };
+    Ok(analysis)
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, (win, score)) in self
+            .win_probabilities
+            .iter()
+            .zip(&self.expected_scores)
+            .enumerate()
+        {
+            writeln!(
+                f,
+                "Player {} wins with probability {} (~{:.6}), expected score {:.3}",
+                index + 1,
+                win,
+                win.to_f64(),
+                score.to_f64()
+            )?;
+        }
+        for (turns, probability) in &self.turns {
+            writeln!(
+                f,
+                "Ends after {} turns with probability {}",
+                turns, probability
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Parse a line such as `Player 1 starting position: 4`, returning the player number as well.
+fn read_player(line: &str) -> Result<(usize, Player), ParseError> {
+    let mut words = line.split_whitespace();
+    expect_word(line, &mut words, "Player")?;
+    let number = next_word(line, &mut words, "a player number")?;
+    expect_word(line, &mut words, "starting")?;
+    expect_word(line, &mut words, "position:")?;
+    // Board spaces are numbered from one, and both games are played on the same board.
+    let board_size = Rules::PRACTICE.board_size;
+    let what = format!("a starting position from 1 to {}", board_size);
+    // Look ahead at the position, to point at it if it's off the board.
+    let word = words.clone().next().unwrap_or_default();
+    let position: NonZeroUsize = next_word(line, &mut words, &what)?;
+    if position.get() > board_size {
+        let error = ParseError::new(format!("expected {}, found `{}`", what, word));
+        return Err(error.column_offset(offset(line, word)));
+    }
+    Ok((number, Player::new(position.get())))
+}
+
+const EXAMPLE: &str = "\
+Player 1 starting position: 4
+Player 2 starting position: 8
+";
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<Player>;
+    type PartOne = usize;
+    type PartTwo = Result<u128, Overflow>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let players = parse_lines(input, read_player)?;
+        for (index, &(number, _)) in players.iter().enumerate() {
+            if number != index + 1 {
+                let message = format!("expected player {}, found player {}", index + 1, number);
+                return Err(ParseError::new(message).line_offset(index));
+            }
+        }
+        if players.len() < 2 {
+            return Err(ParseError::new(format!(
+                "expected at least two players, found {}",
+                players.len()
+            )));
+        }
+        let players = players.into_iter().map(|(_, player)| player).collect();
+        Ok(players)
+    }
+
+    fn part_one(players: &Self::Input) -> Self::PartOne {
+        play_practice(players, &Rules::PRACTICE).result()
+    }
+
+    fn part_two(players: &Self::Input) -> Self::PartTwo {
+        let wins = play_dirac(players, &Rules::DIRAC)?;
+        Ok(wins.into_iter().max().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day21::parse(EXAMPLE).unwrap();
+        assert_eq!(Day21::part_one(&input), 739785);
+        assert_eq!(Day21::part_two(&input), Ok(444356092776315));
+    }
+
+    #[test]
+    fn wins_per_player() {
+        let input = Day21::parse(EXAMPLE).unwrap();
+        let practice = play_practice(&input, &Rules::PRACTICE);
+        assert_eq!(practice.wins(), [1, 0]);
+        assert_eq!(practice.scores, [1000, 745]);
+        assert_eq!(practice.rolls, 993);
+
+        let wins = play_dirac(&input, &Rules::DIRAC).unwrap();
+        assert_eq!(wins, [444356092776315, 341960390180808]);
+    }
+
+    #[test]
+    fn three_players() {
+        let players = vec![Player::new(4), Player::new(8), Player::new(1)];
+        let practice = play_practice(&players, &Rules::PRACTICE);
+        assert_eq!(practice.wins().iter().sum::<u64>(), 1);
+        assert!(practice.scores[practice.winner] >= 1000);
+
+        // With a one-sided die everyone moves one space a turn, so the first player wins the race
+        // to three points from equal starting positions.
+        let rules = Rules {
+            board_size: 10,
+            winning_score: 3,
+            die_faces: 1,
+            rolls_per_turn: 1,
+        };
+        let players = vec![Player::new(1); 3];
+        assert_eq!(play_dirac(&players, &rules), Ok(vec![1, 0, 0]));
+
+        // Far more universes than fit in 64 bits.
+        let players = vec![Player::new(4), Player::new(8), Player::new(1)];
+        let wins = play_dirac(&players, &Rules::DIRAC).unwrap();
+        let expected = [
+            278122794029600828547,
+            253477283751906861031,
+            302796583677595829173,
+        ];
+        assert_eq!(wins, expected);
+    }
+
+    #[test]
+    fn too_many_universes() {
+        let rules = Rules {
+            winning_score: 1000,
+            ..Rules::DIRAC
+        };
+        let players = [Player::new(4), Player::new(8)];
+        assert_eq!(play_dirac(&players, &rules), Err(Overflow));
+    }
+
+    fn total<'a>(fractions: impl IntoIterator<Item = &'a Fraction>) -> Fraction {
+        fractions
+            .into_iter()
+            .try_fold(Fraction::default(), |total, &fraction| {
+                total.checked_add(fraction)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn analysis() {
+        // The first player always wins on their first turn, landing on two or three.
+        let rules = Rules {
+            board_size: 10,
+            winning_score: 1,
+            die_faces: 2,
+            rolls_per_turn: 1,
+        };
+        let analysis = analyse(&[Player::new(1), Player::new(5)], &rules).unwrap();
+        assert_eq!(
+            analysis.win_probabilities,
+            [Fraction::new(1, 1), Fraction::new(0, 1)]
+        );
+        assert_eq!(analysis.turns, BTreeMap::from([(1, Fraction::new(1, 1))]));
+        assert_eq!(
+            analysis.expected_scores,
+            [Fraction::new(5, 2), Fraction::new(0, 1)]
+        );
+
+        let input = Day21::parse(EXAMPLE).unwrap();
+        let analysis = analyse(&input, &Rules::DIRAC).unwrap();
+        let one = Fraction::new(1, 1);
+        assert_eq!(total(&analysis.win_probabilities), one);
+        assert_eq!(total(analysis.turns.values()), one);
+    }
+
+    #[test]
+    fn three_player_analysis() {
+        // The longest games run to 27 turns, and `27^27` doesn't fit in 128 bits.
+        let players = [Player::new(4), Player::new(8), Player::new(1)];
+        let analysis = analyse(&players, &Rules::DIRAC).unwrap();
+        assert_eq!(analysis.turns.keys().last(), Some(&27));
+        let one = Fraction::new(1, 1);
+        assert_eq!(total(&analysis.win_probabilities), one);
+        assert_eq!(total(analysis.turns.values()), one);
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert_eq!(Rules::new(0, 21, 3, 3), Err(InvalidRules::EmptyBoard));
+        assert_eq!(Rules::new(10, 21, 0, 3), Err(InvalidRules::FacelessDie));
+        assert_eq!(Rules::new(10, 21, 3, 3), Ok(Rules::DIRAC));
+    }
+
+    #[test]
+    fn off_the_board() {
+        let error = Day21::parse("Player 1 starting position: 4\nPlayer 2 starting position: 11\n")
+            .err()
+            .unwrap();
+        assert_eq!(error.line, Some(2));
+        assert_eq!(error.column, Some(29));
+        assert_eq!(
+            error.message,
+            "expected a starting position from 1 to 10, found `11`"
+        );
+    }
+
+    #[test]
+    fn roll_distribution() {
+        let expected = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+        assert_eq!(Rules::DIRAC.roll_distribution(), expected);
+    }
 }
@@ -1,3 +1,5 @@
+use crate::error::{next_word, offset, parse_lines};
+use crate::{ParseError, Solution};
 use std::collections::HashMap;
 use std::str::FromStr;
 
@@ -8,20 +10,23 @@ enum Action {
 }
 
 impl FromStr for Action {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let action = match s {
             "on" => Action::On,
             "off" => Action::Off,
-            _ => return Err(()),
+            _ => {
+                let message = format!("expected `on` or `off`, found `{}`", s);
+                return Err(ParseError::new(message));
+            }
         };
         Ok(action)
     }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct Cuboid {
+pub struct Cuboid {
     min_x: i64,
     max_x: i64,
     min_y: i64,
@@ -81,21 +86,35 @@ impl Cuboid {
     }
 }
 
+// Read a range such as `x=10..12` from an iterator over slices of `s`.
+fn read_range<'a>(
+    s: &str,
+    ranges: &mut impl Iterator<Item = &'a str>,
+    axis: char,
+) -> Result<(i64, i64), ParseError> {
+    let prefix = format!("{}=", axis);
+    let missing = || ParseError::new(format!("expected `{}` range in cuboid", prefix));
+    let range = ranges
+        .next()
+        .ok_or_else(|| missing().column_offset(s.len()))?;
+    let bounds = range
+        .strip_prefix(&prefix)
+        .ok_or_else(|| missing().column_offset(offset(s, range)))?;
+
+    let mut bounds = bounds.split("..");
+    let min = next_word(s, &mut bounds, "a lower bound")?;
+    let max = next_word(s, &mut bounds, "an upper bound")?;
+    Ok((min, max))
+}
+
 impl FromStr for Cuboid {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // yuk.
         let mut ranges = s.split(',');
-        let mut xrange = ranges.next().ok_or(())?[2..].split("..");
-        let min_x = xrange.next().ok_or(())?.parse().map_err(|_| ())?;
-        let max_x = xrange.next().ok_or(())?.parse().map_err(|_| ())?;
-        let mut yrange = ranges.next().ok_or(())?[2..].split("..");
-        let min_y = yrange.next().ok_or(())?.parse().map_err(|_| ())?;
-        let max_y = yrange.next().ok_or(())?.parse().map_err(|_| ())?;
-        let mut zrange = ranges.next().ok_or(())?[2..].split("..");
-        let min_z = zrange.next().ok_or(())?.parse().map_err(|_| ())?;
-        let max_z = zrange.next().ok_or(())?.parse().map_err(|_| ())?;
+        let (min_x, max_x) = read_range(s, &mut ranges, 'x')?;
+        let (min_y, max_y) = read_range(s, &mut ranges, 'y')?;
+        let (min_z, max_z) = read_range(s, &mut ranges, 'z')?;
 
         let cuboid = Cuboid::new(min_x, max_x, min_y, max_y, min_z, max_z);
         Ok(cuboid)
@@ -103,7 +122,7 @@ impl FromStr for Cuboid {
 }
 
 #[derive(Debug)]
-struct Instruction {
+pub struct Instruction {
     action: Action,
     cuboid: Cuboid,
 }
@@ -115,24 +134,26 @@ impl Instruction {
 }
 
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut words = s.split_whitespace();
-        let action = words.next().ok_or(())?.parse()?;
-        let cuboid = words.next().ok_or(())?.parse()?;
+        let (action, cuboid) = s
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new("expected an action followed by a cuboid"))?;
+        let action = action.parse()?;
+        let cuboid = cuboid
+            .parse()
+            .map_err(|error: ParseError| error.column_offset(offset(s, cuboid)))?;
 
         let instruction = Instruction::new(action, cuboid);
         Ok(instruction)
     }
 }
 
-pub(crate) fn day22() {
-    let input = std::fs::read_to_string("data/day22.txt").unwrap();
-    let instructions: Vec<Instruction> = input.lines().map(|line| line.parse().unwrap()).collect();
-
+// Signed contributions of cuboids, such that summing over them gives the lit region.
+fn contributions(instructions: &[Instruction]) -> HashMap<Cuboid, i64> {
     let mut contributions: HashMap<Cuboid, i64> = HashMap::default();
-    for instruction in &instructions {
+    for instruction in instructions {
         let mut intersections: HashMap<Cuboid, i64> = HashMap::default();
         for (cuboid, value) in &contributions {
             if let Some(intersection) = cuboid.intersect(&instruction.cuboid) {
@@ -148,16 +169,53 @@ pub(crate) fn day22() {
         }
         contributions.retain(|_, v| *v != 0);
     }
+    contributions
+}
+
+const EXAMPLE: &str = "\
+on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10
+";
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Vec<Instruction>;
+    type PartOne = i64;
+    type PartTwo = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_lines(input, str::parse)
+    }
 
-    let part_one: i64 = contributions
-        .iter()
-        .map(|(cuboid, value)| cuboid.size_part_one() * value)
-        .sum();
-    println!("Part two answer is {}", part_one);
-
-    let part_two: i64 = contributions
-        .iter()
-        .map(|(cuboid, value)| cuboid.size() * value)
-        .sum();
-    println!("Part two answer is {}", part_two);
+    fn part_one(instructions: &Self::Input) -> Self::PartOne {
+        contributions(instructions)
+            .iter()
+            .map(|(cuboid, value)| cuboid.size_part_one() * value)
+            .sum()
+    }
+
+    fn part_two(instructions: &Self::Input) -> Self::PartTwo {
+        contributions(instructions)
+            .iter()
+            .map(|(cuboid, value)| cuboid.size() * value)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day22::parse(EXAMPLE).unwrap();
+        assert_eq!(Day22::part_one(&input), 39);
+        assert_eq!(Day22::part_two(&input), 39);
+    }
 }
@@ -1,263 +1,909 @@
-use maplit::hashmap;
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use crate::search::{self, Problem};
+use crate::{ParseError, Solution};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// An amphipod, identified by its kind: `A` is kind zero, `B` kind one and so on.
+///
+/// Each kind of amphipod belongs in the room with the same index, counting from the left.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Amphipod(usize);
 
-const CORRIDOR_X: [usize; 7] = [0, 1, 3, 5, 7, 9, 10];
-const ROOM_X: [usize; 4] = [2, 4, 6, 8];
+impl TryFrom<char> for Amphipod {
+    type Error = ParseError;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum Amphipod {
-    Amber,
-    Bronze,
-    Copper,
-    Desert,
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        if !c.is_ascii_uppercase() {
+            let message = format!("expected an amphipod, found `{}`", c);
+            return Err(ParseError::new(message));
+        }
+        Ok(Self(usize::from(c as u8 - b'A')))
+    }
 }
 
 impl Amphipod {
     fn home_room(&self) -> usize {
-        match self {
-            Self::Amber => 0,
-            Self::Bronze => 1,
-            Self::Copper => 2,
-            Self::Desert => 3,
-        }
+        self.0
+    }
+
+    fn letter(&self) -> char {
+        (b'A' + self.0 as u8) as char
+    }
+}
+
+impl fmt::Display for Amphipod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.letter())
     }
-    fn step_cost(&self) -> usize {
+}
+
+/// Somewhere an amphipod can be.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Place {
+    /// Counting from the left of the hallway, from zero.
+    Hallway(usize),
+    /// Counting rooms from the left and depth from the hallway down, both from zero.
+    Room { room: usize, depth: usize },
+}
+
+impl fmt::Display for Place {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Amber => 1,
-            Self::Bronze => 10,
-            Self::Copper => 100,
-            Self::Desert => 1000,
+            Self::Hallway(x) => write!(f, "hallway {}", x),
+            Self::Room { room, depth } => write!(f, "room {} depth {}", room, depth),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
-struct Room {
-    amphipods: Vec<Option<Amphipod>>,
+/// An amphipod moving from one place to another.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Move {
+    pub amphipod: Amphipod,
+    pub from: Place,
+    pub to: Place,
+    pub energy: usize,
 }
 
-impl Room {
-    fn new(amphipods: Vec<Amphipod>) -> Self {
-        let amphipods = amphipods.into_iter().map(Some).collect();
-        Self { amphipods }
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} from {} to {} using {} energy",
+            self.amphipod, self.from, self.to, self.energy
+        )
     }
+}
 
-    fn is_full(&self, room_size: usize, amphipod: Amphipod) -> bool {
-        self.amphipods
-            .iter()
-            .take(room_size)
-            .all(|a| a == &Some(amphipod))
+/// A cheapest way to organize the amphipods.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Plan {
+    pub energy: usize,
+    pub moves: Vec<Move>,
+    /// Where the amphipods are before the first move and after each one.
+    pub maps: Vec<Map>,
+}
+
+/// There is no way to organize the amphipods.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Unsolvable;
+
+impl fmt::Display for Unsolvable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the amphipods cannot be organized")
     }
+}
 
-    fn has_other(&self, amphipod: Amphipod) -> bool {
-        self.amphipods
-            .iter()
-            .any(|amp| amp.map_or(false, |other| other != amphipod))
+impl std::error::Error for Unsolvable {}
+
+/// The step costs given don't match the kinds of amphipod in a burrow.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct WrongStepCosts {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for WrongStepCosts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected {} step costs, one for each kind of amphipod, found {}",
+            self.expected, self.found
+        )
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
-struct Map {
-    rooms: [Room; 4],
-    corridor: [Option<Amphipod>; 7],
+impl std::error::Error for WrongStepCosts {}
+
+/// The shape of a burrow, and how much energy each kind of amphipod takes per step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Layout {
+    hallway_length: usize,
+    // The position in the hallway just outside each room.
+    doorways: Vec<usize>,
+    // How many amphipods fit in each room.
+    depth: usize,
+    step_costs: Vec<usize>,
 }
 
-impl Map {
-    fn new(rooms: [Room; 4]) -> Self {
-        let corridor = [None; 7];
-        Self { rooms, corridor }
+impl Layout {
+    fn rooms(&self) -> usize {
+        self.doorways.len()
     }
 
-    fn finished(&self, room_size: usize) -> bool {
-        self.rooms
-            .iter()
-            .zip([
-                Amphipod::Amber,
-                Amphipod::Bronze,
-                Amphipod::Copper,
-                Amphipod::Desert,
-            ])
-            .all(|(room, amphipod)| room.is_full(room_size, amphipod))
+    fn is_doorway(&self, x: usize) -> bool {
+        self.doorways.contains(&x)
     }
 
-    fn corridor_occupied(&self, lo_x: usize, hi_x: usize) -> bool {
-        self.corridor
-            .iter()
-            .zip(CORRIDOR_X)
-            .any(|(amphipod, cx)| amphipod.is_some() && lo_x <= cx && cx <= hi_x)
+    fn places(&self) -> impl Iterator<Item = Place> + '_ {
+        let hallway = (0..self.hallway_length).map(Place::Hallway);
+        let rooms = (0..self.rooms())
+            .flat_map(move |room| (0..self.depth).map(move |depth| Place::Room { room, depth }));
+        hallway.chain(rooms)
+    }
+
+    // Each place is stored in a few bits of a `Map`: enough to count from zero, for an empty place,
+    // up to the number of kinds of amphipod.
+    fn bits(&self) -> u32 {
+        usize::BITS - self.rooms().leading_zeros()
+    }
+
+    // How many places a `Map` fits in each of its integers, with none split between two.
+    fn places_per_word(&self) -> usize {
+        (u128::BITS / self.bits()) as usize
+    }
+
+    // How many integers a `Map` of this burrow needs.
+    fn words(&self) -> usize {
+        let places = self.hallway_length + self.rooms() * self.depth;
+        places.div_ceil(self.places_per_word())
+    }
+
+    // Which place in a `Map` this is, counting along the hallway and then through each room in
+    // turn.
+    fn index(&self, place: Place) -> usize {
+        match place {
+            Place::Hallway(x) => x,
+            Place::Room { room, depth } => self.hallway_length + room * self.depth + depth,
+        }
+    }
+
+    // Which integer of a `Map` holds a place, and how far along it.
+    fn location(&self, place: Place) -> (usize, u32) {
+        let index = self.index(place);
+        let per_word = self.places_per_word();
+        (index / per_word, (index % per_word) as u32 * self.bits())
+    }
+
+    // Where in the hallway a place is, and how many steps into a room.
+    fn position(&self, place: Place) -> (usize, usize) {
+        match place {
+            Place::Hallway(x) => (x, 0),
+            Place::Room { room, depth } => (self.doorways[room], depth + 1),
+        }
+    }
+
+    // The number of steps between two places, going by way of the hallway.
+    fn distance(&self, from: Place, to: Place) -> usize {
+        let (from_x, from_depth) = self.position(from);
+        let (to_x, to_depth) = self.position(to);
+        from_depth + from_x.abs_diff(to_x) + to_depth
     }
 }
 
+/// Where each amphipod is, packed into integers according to the burrow's [`Layout`].
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
-struct State {
-    map: Map,
-    cost: usize,
+pub struct Map(Words);
+
+// Most burrows fit in a single integer, which is much quicker to copy and compare than a vector.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Words {
+    One(u128),
+    Many(Vec<u128>),
 }
 
-fn diff(x: usize, y: usize) -> usize {
-    if x < y {
-        y - x
-    } else {
-        x - y
+// Every map of a burrow uses the same variant, so there's no need to hash which one it is.
+impl Hash for Words {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Self::One(word) => word.hash(state),
+            Self::Many(words) => words.hash(state),
+        }
     }
 }
 
-impl State {
-    fn new(map: Map, cost: usize) -> Self {
-        Self { map, cost }
+impl Map {
+    // Pack the contents of the hallway and of each room, from the hallway down, into a map.
+    fn pack(
+        layout: &Layout,
+        hallway: &[Option<Amphipod>],
+        rooms: &[Vec<Option<Amphipod>>],
+    ) -> Self {
+        let mut map = match layout.words() {
+            1 => Self(Words::One(0)),
+            words => Self(Words::Many(vec![0; words])),
+        };
+        for (x, &amphipod) in hallway.iter().enumerate() {
+            map.set(layout, Place::Hallway(x), amphipod);
+        }
+        for (room, amphipods) in rooms.iter().enumerate() {
+            for (depth, &amphipod) in amphipods.iter().enumerate() {
+                map.set(layout, Place::Room { room, depth }, amphipod);
+            }
+        }
+        map
     }
 
-    fn finished(&self, room_size: usize) -> bool {
-        self.map.finished(room_size)
+    // The integer holding a place, and how far along it the place is.
+    fn word(&self, layout: &Layout, place: Place) -> (u128, u32) {
+        match &self.0 {
+            Words::One(word) => (*word, layout.index(place) as u32 * layout.bits()),
+            Words::Many(words) => {
+                let (index, shift) = layout.location(place);
+                (words[index], shift)
+            }
+        }
     }
 
-    fn neighbours(&self, room_size: usize) -> Vec<Self> {
-        // Consider putting a corridor amphipod in its home room.
-        for (ci, amphipod) in self.map.corridor.iter().enumerate() {
-            if let Some(amphipod) = amphipod {
-                // Never block in something that's not at home.
-                let home_room = amphipod.home_room();
-                if self.map.rooms[home_room].has_other(*amphipod) {
-                    continue;
-                }
+    fn word_mut(&mut self, layout: &Layout, place: Place) -> (&mut u128, u32) {
+        match &mut self.0 {
+            Words::One(word) => (word, layout.index(place) as u32 * layout.bits()),
+            Words::Many(words) => {
+                let (index, shift) = layout.location(place);
+                (&mut words[index], shift)
+            }
+        }
+    }
 
-                // Don't pass through other amphipods.
-                let rx = ROOM_X[home_room];
-                let cx = CORRIDOR_X[ci];
-                let (lo, hi) = if rx < cx { (rx, cx - 1) } else { (cx + 1, rx) };
-                if self.map.corridor_occupied(lo, hi) {
-                    continue;
-                }
+    fn get(&self, layout: &Layout, place: Place) -> Option<Amphipod> {
+        let bits = layout.bits();
+        let (word, shift) = self.word(layout, place);
+        let value = (word >> shift) & ((1 << bits) - 1);
+        (value as usize).checked_sub(1).map(Amphipod)
+    }
+
+    fn set(&mut self, layout: &Layout, place: Place, amphipod: Option<Amphipod>) {
+        let bits = layout.bits();
+        let value = amphipod.map_or(0, |amphipod| amphipod.0 as u128 + 1);
+        let (word, shift) = self.word_mut(layout, place);
+        *word = (*word & !(((1 << bits) - 1) << shift)) | (value << shift);
+    }
+
+    // This map, with the amphipod at `from` moved to `to`.
+    fn moved(&self, layout: &Layout, from: Place, to: Place) -> Self {
+        let mut map = self.clone();
+        let amphipod = map.get(layout, from);
+        map.set(layout, from, None);
+        map.set(layout, to, amphipod);
+        map
+    }
+
+    // The contents of a room, from the hallway down.
+    fn room<'a>(
+        &'a self,
+        layout: &'a Layout,
+        room: usize,
+    ) -> impl Iterator<Item = Option<Amphipod>> + 'a {
+        (0..layout.depth).map(move |depth| self.get(layout, Place::Room { room, depth }))
+    }
+
+    fn finished(&self, layout: &Layout) -> bool {
+        (0..layout.rooms()).all(|room| {
+            self.room(layout, room)
+                .all(|amphipod| amphipod == Some(Amphipod(room)))
+        })
+    }
+
+    // Whether the room has any amphipods other than `amphipod` in it.
+    fn has_other(&self, layout: &Layout, room: usize, amphipod: Amphipod) -> bool {
+        self.room(layout, room)
+            .any(|amp| amp.is_some_and(|other| other != amphipod))
+    }
 
-                // Go as far to the back of the room as we can.
-                let occupied = self.map.rooms[home_room]
-                    .amphipods
-                    .iter()
-                    .position(|a| a.is_some())
-                    .unwrap_or(room_size);
-                let back = occupied - 1;
-
-                let distance = back + 1 + diff(rx, cx);
-                let mut new_state = self.clone();
-                new_state.map.rooms[home_room].amphipods[back] = new_state.map.corridor[ci].take();
-                new_state.cost = self.cost + amphipod.step_cost() * distance;
-
-                // If we can do this, it's definitely a good move; no need to consider further
-                // neighbours.
-                return vec![new_state];
+    // How deep the amphipod nearest the hallway in a room is.
+    fn front(&self, layout: &Layout, room: usize) -> Option<usize> {
+        self.room(layout, room).position(|a| a.is_some())
+    }
+
+    // The move that takes this map to `next`, which must be one move away.
+    fn move_to(&self, next: &Self, layout: &Layout) -> Move {
+        let from = layout
+            .places()
+            .find(|&place| self.get(layout, place).is_some() && next.get(layout, place).is_none())
+            .unwrap();
+        let to = layout
+            .places()
+            .find(|&place| self.get(layout, place).is_none() && next.get(layout, place).is_some())
+            .unwrap();
+        let amphipod = self.get(layout, from).unwrap();
+        let energy = layout.step_costs[amphipod.home_room()] * layout.distance(from, to);
+        Move {
+            amphipod,
+            from,
+            to,
+            energy,
+        }
+    }
+
+    // Whether the hallway is empty from just after `from` up to and including `to`.
+    fn hallway_clear(&self, layout: &Layout, from: usize, to: usize) -> bool {
+        let mut path = if from < to {
+            from + 1..to + 1
+        } else {
+            to..from
+        };
+        path.all(|x| self.get(layout, Place::Hallway(x)).is_none())
+    }
+
+    // Where `amphipod`, standing in the hallway at `x`, would stop in its home room, and how many
+    // steps it would take to get there.
+    fn route_home(&self, layout: &Layout, amphipod: Amphipod, x: usize) -> Option<(usize, usize)> {
+        // Never block in something that's not at home.
+        let home = amphipod.home_room();
+        if self.has_other(layout, home, amphipod) {
+            return None;
+        }
+
+        // Don't pass through other amphipods.
+        let doorway = layout.doorways[home];
+        if !self.hallway_clear(layout, x, doorway) {
+            return None;
+        }
+
+        // Go as far to the back of the room as we can.
+        let back = self
+            .front(layout, home)
+            .unwrap_or(layout.depth)
+            .checked_sub(1)?;
+        let steps = x.abs_diff(doorway) + back + 1;
+        Some((back, steps))
+    }
+
+    // The maps reachable in one move, with the energy each move takes.
+    fn moves(&self, layout: &Layout) -> Vec<(Self, usize)> {
+        // Consider putting an amphipod in its home room, either from the hallway or straight from
+        // another room. If we can do this, it's definitely a good move; no need to consider
+        // further neighbours.
+        for x in 0..layout.hallway_length {
+            if let Some(amphipod) = self.get(layout, Place::Hallway(x)) {
+                if let Some((back, steps)) = self.route_home(layout, amphipod, x) {
+                    let room = amphipod.home_room();
+                    let home = Place::Room { room, depth: back };
+                    let energy = layout.step_costs[room] * steps;
+                    return vec![(self.moved(layout, Place::Hallway(x), home), energy)];
+                }
             }
         }
 
-        // Consider leaving a room.
-        let mut neighbours = vec![];
-        for (ri, room) in self.map.rooms.iter().enumerate() {
-            if let Some(front) = room.amphipods.iter().position(|a| a.is_some()) {
-                let amphipod = room.amphipods[front].unwrap();
+        let leavers: Vec<(usize, usize, Amphipod)> = (0..layout.rooms())
+            .filter_map(|ri| {
+                let front = self.front(layout, ri)?;
+                let amphipod = self.get(
+                    layout,
+                    Place::Room {
+                        room: ri,
+                        depth: front,
+                    },
+                )?;
 
                 // Don't move if we're already at home, and not blocking anything.
-                let home_room = amphipod.home_room();
-                if ri == home_room && !room.has_other(amphipod) {
-                    continue;
+                if ri == amphipod.home_room() && !self.has_other(layout, ri, amphipod) {
+                    return None;
                 }
+                Some((ri, front, amphipod))
+            })
+            .collect();
+
+        for &(ri, front, amphipod) in &leavers {
+            let doorway = layout.doorways[ri];
+            if let Some((back, steps)) = self.route_home(layout, amphipod, doorway) {
+                let room = amphipod.home_room();
+                let from = Place::Room {
+                    room: ri,
+                    depth: front,
+                };
+                let home = Place::Room { room, depth: back };
+                let energy = layout.step_costs[room] * (front + 1 + steps);
+                return vec![(self.moved(layout, from, home), energy)];
+            }
+        }
 
-                // Consider the possible places to enter the corridor.
-                let rx = ROOM_X[ri];
-                for (ci, &cx) in CORRIDOR_X.iter().enumerate() {
-                    // Don't pass through other amphipods.
-                    let (lo, hi) = if rx < cx { (rx, cx) } else { (cx, rx) };
-                    if self.map.corridor_occupied(lo, hi) {
-                        continue;
-                    }
-
-                    let distance = 1 + front + diff(rx, cx);
-                    let mut new_state = self.clone();
-                    new_state.map.corridor[ci] = new_state.map.rooms[ri].amphipods[front].take();
-                    new_state.cost = self.cost + amphipod.step_cost() * distance;
-                    neighbours.push(new_state);
+        // Consider the possible places to stop in the hallway.
+        let mut neighbours = vec![];
+        for &(ri, front, amphipod) in &leavers {
+            let doorway = layout.doorways[ri];
+            let from = Place::Room {
+                room: ri,
+                depth: front,
+            };
+            for x in 0..layout.hallway_length {
+                // Never stop just outside a room, and don't pass through other amphipods.
+                if layout.is_doorway(x) || !self.hallway_clear(layout, doorway, x) {
+                    continue;
                 }
+
+                let distance = front + 1 + doorway.abs_diff(x);
+                let new_map = self.moved(layout, from, Place::Hallway(x));
+                let energy = layout.step_costs[amphipod.home_room()] * distance;
+                neighbours.push((new_map, energy));
             }
         }
 
         neighbours
     }
+
+    // A lower bound on the energy still needed, ignoring amphipods getting in each other's way.
+    //
+    // Every amphipod that is not yet settled at the back of its home room must walk to the doorway
+    // of that room, stepping at least one place aside and back if it starts in there. Then the
+    // amphipods entering each room fill its places nearest the hallway.
+    fn energy_needed(&self, layout: &Layout) -> usize {
+        let mut energy = 0;
+        let mut entering = vec![0; layout.rooms()];
+        let mut walk = |amphipod: Amphipod, steps: usize| {
+            let home = amphipod.home_room();
+            energy += layout.step_costs[home] * steps;
+            entering[home] += 1;
+        };
+
+        for x in 0..layout.hallway_length {
+            if let Some(amphipod) = self.get(layout, Place::Hallway(x)) {
+                walk(amphipod, x.abs_diff(layout.doorways[amphipod.home_room()]));
+            }
+        }
+
+        for room in 0..layout.rooms() {
+            let contents: Vec<Option<Amphipod>> = self.room(layout, room).collect();
+            let settled = contents
+                .iter()
+                .rev()
+                .take_while(|&&amphipod| amphipod == Some(Amphipod(room)))
+                .count();
+            for (depth, amphipod) in contents[..layout.depth - settled].iter().enumerate() {
+                if let Some(amphipod) = *amphipod {
+                    let home = amphipod.home_room();
+                    let across = if home == room {
+                        2
+                    } else {
+                        layout.doorways[room].abs_diff(layout.doorways[home])
+                    };
+                    walk(amphipod, depth + 1 + across);
+                }
+            }
+        }
+
+        let filling: usize = entering
+            .iter()
+            .zip(&layout.step_costs)
+            .map(|(count, cost)| cost * count * (count + 1) / 2)
+            .sum();
+        energy + filling
+    }
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
+/// A burrow of amphipods to organize.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Burrow {
+    layout: Layout,
+    map: Map,
+}
+
+// Read one of the walls at the top or bottom of the burrow, which must consist only of `#`.
+fn expect_wall(index: usize, line: &str) -> Result<(), ParseError> {
+    match line.trim().char_indices().find(|&(_, c)| c != '#') {
+        _ if line.trim().is_empty() => {
+            Err(ParseError::new("expected a wall, found a blank line").line_offset(index))
+        }
+        Some((column, c)) => {
+            let column = column + line.len() - line.trim_start().len();
+            let message = format!("expected `#`, found `{}`", c);
+            Err(ParseError::new(message)
+                .column_offset(column)
+                .line_offset(index))
+        }
+        None => Ok(()),
     }
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+// Parse the contents of one place in the burrow, at `column` of line `index`.
+fn read_place(index: usize, line: &str, column: usize) -> Result<Option<Amphipod>, ParseError> {
+    let place = match line.get(column..).and_then(|rest| rest.chars().next()) {
+        Some('.') => Ok(None),
+        Some(c) => Amphipod::try_from(c).map(Some),
+        None => Err(ParseError::new("expected an amphipod, found end of line")),
+    };
+    place.map_err(|error| error.column_offset(column).line_offset(index))
+}
+
+impl FromStr for Burrow {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().collect();
+        let line = |index: usize| {
+            lines
+                .get(index)
+                .copied()
+                .ok_or_else(|| ParseError::new("expected more of the burrow, found end of input"))
+        };
+
+        expect_wall(0, line(0)?)?;
+        let hallway_line = line(1)?.trim_end();
+        if hallway_line.len() < 3 || !hallway_line.starts_with('#') || !hallway_line.ends_with('#')
+        {
+            let error = ParseError::new("expected a hallway between walls");
+            return Err(error.line_offset(1));
+        }
+        // The burrow's outer wall is one column to the left of the hallway.
+        let hallway_length = hallway_line.len() - 2;
+        let hallway = (0..hallway_length)
+            .map(|x| read_place(1, hallway_line, x + 1))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The first line of the rooms tells us where the doorways are.
+        let first_row = line(2)?;
+        let doorways: Vec<usize> = (0..hallway_length)
+            .filter(|&x| !matches!(first_row.as_bytes().get(x + 1), None | Some(b'#' | b' ')))
+            .collect();
+        if doorways.is_empty() {
+            return Err(ParseError::new("expected rooms below the hallway").line_offset(2));
+        }
+
+        // Each kind of amphipod takes ten times the energy per step of the one before.
+        let step_costs: Vec<usize> = (0..doorways.len() as u32)
+            .map(|kind| 10_usize.checked_pow(kind))
+            .collect::<Option<_>>()
+            .ok_or_else(|| {
+                let message = format!(
+                    "expected at most {} rooms, found {}",
+                    usize::MAX.ilog10() + 1,
+                    doorways.len()
+                );
+                ParseError::new(message).line_offset(2)
+            })?;
+
+        // Rooms are read from the hallway down, until the wall at the bottom.
+        let mut rooms = vec![vec![]; doorways.len()];
+        let mut index = 2;
+        while index == 2 || line(index)?.contains(|c| c != '#' && c != ' ') {
+            for (room, &x) in rooms.iter_mut().zip(&doorways) {
+                room.push(read_place(index, lines[index], x + 1)?);
+            }
+            index += 1;
+        }
+        expect_wall(index, lines[index])?;
+
+        // Every amphipod must have a room to go home to.
+        let kinds = doorways.len();
+        for (index, line) in lines.iter().enumerate().take(index) {
+            if let Some((column, c)) = line
+                .char_indices()
+                .find(|&(_, c)| c.is_ascii_uppercase() && usize::from(c as u8 - b'A') >= kinds)
+            {
+                let last = (b'A' + kinds as u8 - 1) as char;
+                let message = format!("expected an amphipod from `A` to `{}`, found `{}`", last, c);
+                let error = ParseError::new(message).column_offset(column);
+                return Err(error.line_offset(index));
+            }
+        }
+
+        let layout = Layout {
+            hallway_length,
+            doorways,
+            depth: rooms[0].len(),
+            step_costs,
+        };
+        let map = Map::pack(&layout, &hallway, &rooms);
+        Ok(Self { layout, map })
     }
 }
 
-fn solve(map: Map, room_size: usize) -> usize {
-    let start = State::new(map, 0);
-    let mut costs = hashmap! { start.map.clone() => 0 };
-    let mut queue = BinaryHeap::new();
-    queue.push(start);
+impl Burrow {
+    /// The same burrow, with each kind of amphipod taking the given energy per step rather than
+    /// `A` taking 1, `B` 10, `C` 100 and so on.
+    ///
+    /// There must be one cost for each kind of amphipod.
+    pub fn with_step_costs(mut self, step_costs: Vec<usize>) -> Result<Self, WrongStepCosts> {
+        if step_costs.len() != self.layout.rooms() {
+            return Err(WrongStepCosts {
+                expected: self.layout.rooms(),
+                found: step_costs.len(),
+            });
+        }
+        self.layout.step_costs = step_costs;
+        Ok(self)
+    }
 
-    while let Some(state) = queue.pop() {
-        if state.finished(room_size) {
-            return state.cost;
+    // The full diagram, with these extra lines inserted below the first line of each room. Only
+    // burrows with four rooms can be unfolded.
+    //
+    //   #D#C#B#A#
+    //   #D#B#A#C#
+    fn unfolded(&self) -> Option<Self> {
+        let layout = &self.layout;
+        if layout.rooms() != 4 {
+            return None;
         }
+        let hallway: Vec<Option<Amphipod>> = (0..layout.hallway_length)
+            .map(|x| self.map.get(layout, Place::Hallway(x)))
+            .collect();
+        let extra = [[3, 3], [2, 1], [1, 0], [0, 2]];
+        let rooms: Vec<Vec<Option<Amphipod>>> = extra
+            .into_iter()
+            .enumerate()
+            .map(|(room, extra)| {
+                let mut amphipods: Vec<_> = self.map.room(layout, room).collect();
+                amphipods.splice(1..1, extra.map(|kind| Some(Amphipod(kind))));
+                amphipods
+            })
+            .collect();
+
+        let layout = Layout {
+            depth: layout.depth + 2,
+            ..layout.clone()
+        };
+        let map = Map::pack(&layout, &hallway, &rooms);
+        Some(Self { layout, map })
+    }
 
-        for neighbour in state.neighbours(room_size) {
-            let current_cost = costs.entry(neighbour.map.clone()).or_insert(usize::MAX);
-            if neighbour.cost < *current_cost {
-                *current_cost = neighbour.cost;
-                queue.push(neighbour);
+    /// A cheapest way to organize the amphipods.
+    pub fn organize(&self) -> Result<Plan, Unsolvable> {
+        let path = search::astar(self).ok_or(Unsolvable)?;
+        let moves = path
+            .states
+            .windows(2)
+            .map(|maps| maps[0].move_to(&maps[1], &self.layout))
+            .collect();
+        let plan = Plan {
+            energy: path.cost,
+            moves,
+            maps: path.states,
+        };
+        Ok(plan)
+    }
+
+    /// The least energy needed to organize the amphipods.
+    pub fn minimum_energy(&self) -> Result<usize, Unsolvable> {
+        self.organize().map(|plan| plan.energy)
+    }
+
+    /// Draw a map of this burrow as in the puzzle description.
+    pub fn render(&self, map: &Map) -> String {
+        let layout = &self.layout;
+        let width = layout.hallway_length + 2;
+        let show = |place| {
+            map.get(layout, place)
+                .map_or('.', |amphipod| amphipod.letter())
+        };
+
+        // Below the top line of the rooms, the burrow's walls only go around the rooms.
+        let first = layout.doorways.iter().min().unwrap() + 1;
+        let last = layout.doorways.iter().max().unwrap() + 1;
+        let wall = |column: usize, outside: char| {
+            if first - 1 <= column && column <= last + 1 {
+                '#'
+            } else {
+                outside
             }
+        };
+
+        let mut lines = vec!["#".repeat(width)];
+        let hallway: String = (0..layout.hallway_length)
+            .map(|x| show(Place::Hallway(x)))
+            .collect();
+        lines.push(format!("#{}#", hallway));
+        for row in 0..layout.depth {
+            let line: String = (0..width)
+                .map(
+                    |column| match layout.doorways.iter().position(|&x| x + 1 == column) {
+                        Some(room) => show(Place::Room { room, depth: row }),
+                        None if row == 0 => '#',
+                        None => wall(column, ' '),
+                    },
+                )
+                .collect();
+            lines.push(line.trim_end().to_owned());
         }
+        let bottom: String = (0..width).map(|column| wall(column, ' ')).collect();
+        lines.push(bottom.trim_end().to_owned());
+
+        let mut picture = lines.join("\n");
+        picture.push('\n');
+        picture
     }
-    usize::MAX
 }
 
-pub(crate) fn day23() {
-    // #############
-    // #...........#
-    // ###D#C#D#B###
-    //   #B#A#A#C#
-    //   #########
-    let amber = Room::new(vec![Amphipod::Desert, Amphipod::Bronze]);
-    let bronze = Room::new(vec![Amphipod::Copper, Amphipod::Amber]);
-    let copper = Room::new(vec![Amphipod::Desert, Amphipod::Amber]);
-    let desert = Room::new(vec![Amphipod::Bronze, Amphipod::Copper]);
-    let map = Map::new([amber, bronze, copper, desert]);
-
-    let cost = solve(map, 2);
-    println!("Part one answer is {}", cost);
-
-    let amber = Room::new(vec![
-        Amphipod::Desert,
-        Amphipod::Desert,
-        Amphipod::Desert,
-        Amphipod::Bronze,
-    ]);
-    let bronze = Room::new(vec![
-        Amphipod::Copper,
-        Amphipod::Copper,
-        Amphipod::Bronze,
-        Amphipod::Amber,
-    ]);
-    let copper = Room::new(vec![
-        Amphipod::Desert,
-        Amphipod::Bronze,
-        Amphipod::Amber,
-        Amphipod::Amber,
-    ]);
-    let desert = Room::new(vec![
-        Amphipod::Bronze,
-        Amphipod::Amber,
-        Amphipod::Copper,
-        Amphipod::Copper,
-    ]);
-    let map = Map::new([amber, bronze, copper, desert]);
-
-    let cost = solve(map, 4);
-    println!("Part two answer is {}", cost);
+// Organize the amphipods, starting from the given map.
+impl Problem for Burrow {
+    type State = Map;
+    type Cost = usize;
+
+    fn start(&self) -> Self::State {
+        self.map.clone()
+    }
+
+    fn neighbours(&self, map: &Self::State, neighbours: &mut Vec<(Self::State, Self::Cost)>) {
+        neighbours.extend(map.moves(&self.layout));
+    }
+
+    fn is_goal(&self, map: &Self::State) -> bool {
+        map.finished(&self.layout)
+    }
+
+    fn heuristic(&self, map: &Self::State) -> Self::Cost {
+        map.energy_needed(&self.layout)
+    }
+}
+
+const EXAMPLE: &str = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########
+";
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = Burrow;
+    type PartOne = Result<usize, Unsolvable>;
+    // Only burrows with four rooms can be unfolded.
+    type PartTwo = Option<Result<usize, Unsolvable>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input.parse()
+    }
+
+    fn part_one(burrow: &Self::Input) -> Self::PartOne {
+        burrow.minimum_energy()
+    }
+
+    fn part_two(burrow: &Self::Input) -> Self::PartTwo {
+        let unfolded = burrow.unfolded()?;
+        Some(unfolded.minimum_energy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day23::parse(EXAMPLE).unwrap();
+        assert_eq!(Day23::part_one(&input), Ok(12521));
+        assert_eq!(Day23::part_two(&input), Some(Ok(44169)));
+    }
+
+    #[test]
+    fn unfold() {
+        let input = Day23::parse(EXAMPLE).unwrap();
+        let unfolded = Day23::parse(
+            "\
+#############
+#...........#
+###B#C#B#D###
+  #D#C#B#A#
+  #D#B#A#C#
+  #A#D#C#A#
+  #########
+",
+        )
+        .unwrap();
+        assert_eq!(input.unfolded(), Some(unfolded));
+    }
+
+    #[test]
+    fn plan() {
+        let input = Day23::parse(EXAMPLE).unwrap();
+        let plan = input.organize().unwrap();
+        assert_eq!(
+            plan.moves.iter().map(|step| step.energy).sum::<usize>(),
+            12521
+        );
+        assert_eq!(plan.maps.len(), plan.moves.len() + 1);
+        assert_eq!(input.render(&plan.maps[0]), EXAMPLE);
+        let organized = "\
+#############
+#...........#
+###A#B#C#D###
+  #A#B#C#D#
+  #########
+";
+        assert_eq!(input.render(plan.maps.last().unwrap()), organized);
+    }
+
+    #[test]
+    fn unsolvable() {
+        let burrow: Burrow = "######\n#....#\n##AA##\n ####\n".parse().unwrap();
+        assert_eq!(burrow.organize(), Err(Unsolvable));
+    }
+
+    #[test]
+    fn other_shapes() {
+        // Three rooms, three deep, with space for two amphipods between rooms.
+        let burrow: Burrow = "\
+###############
+#.............#
+###B###C###A###
+  #A###B###C#
+  #A###B###C#
+  ###########
+"
+        .parse()
+        .unwrap();
+        assert_eq!(burrow.minimum_energy(), Ok(672));
+
+        // Rooms with no hallway space between them, and equal step costs.
+        let burrow: Burrow = "\
+######
+#....#
+##BA##
+ #AB#
+ ####
+"
+        .parse::<Burrow>()
+        .unwrap();
+        let wrong = burrow.clone().with_step_costs(vec![1, 1, 1]);
+        assert_eq!(
+            wrong.err(),
+            Some(WrongStepCosts {
+                expected: 2,
+                found: 3
+            })
+        );
+        let burrow = burrow.with_step_costs(vec![1, 1]).unwrap();
+        assert_eq!(burrow.minimum_energy(), Ok(8));
+    }
+
+    #[test]
+    fn too_many_rooms() {
+        // Twenty-one rooms, and the last kind of amphipod would take 10^20 energy per step.
+        let rooms = 21;
+        let wall = "#".repeat(2 * rooms + 3);
+        let hallway = format!("#{}#", ".".repeat(2 * rooms + 1));
+        let row = format!("#{}##", "#.".repeat(rooms));
+        let input = format!("{}\n{}\n{}\n{}\n", wall, hallway, row, wall);
+        let error = input.parse::<Burrow>().err().unwrap();
+        assert_eq!(error.message, "expected at most 20 rooms, found 21");
+        assert_eq!(error.line, Some(3));
+    }
+
+    #[test]
+    fn heuristic_is_admissible() {
+        let input = Day23::parse(EXAMPLE).unwrap();
+        for burrow in [input.clone(), input.unfolded().unwrap()] {
+            let plan = burrow.organize().unwrap();
+            let mut remaining = plan.energy;
+            for (map, step) in plan.maps.iter().zip(&plan.moves) {
+                assert!(burrow.heuristic(map) <= remaining);
+                remaining -= step.energy;
+            }
+            assert_eq!(burrow.heuristic(plan.maps.last().unwrap()), 0);
+        }
+    }
+
+    #[test]
+    fn large_burrow() {
+        // Too many places for a map to pack into a single integer.
+        let burrow: Burrow = "\
+###############
+#.............#
+###B#A#D#C#E###
+  #A#B#C#D#E#
+  #A#B#C#D#E#
+  #A#B#C#D#E#
+  #A#B#C#D#E#
+  #A#B#C#D#E#
+  ###########
+"
+        .parse()
+        .unwrap();
+        assert!(burrow.layout.words() > 1);
+        assert_eq!(burrow.minimum_energy(), Ok(4646));
+    }
 }
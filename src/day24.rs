@@ -1,87 +1,462 @@
-use itertools::Either;
+use crate::alu::{Fault, Instruction, Program, Register};
+use crate::{ParseError, Solution};
+use std::fmt;
 
-#[derive(Debug)]
-struct BlackBox {
+/// One block of the MONAD program, which reads a digit `w` and updates `z`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct BlackBox {
+    // Whether the block divides `z` by 26.
     pop: bool,
-    b: u32,
-    c: u32,
+    // The number added to `x`.
+    b: i64,
+    // The number added to `y`.
+    c: i64,
 }
 
-impl BlackBox {
-    fn new(pop: bool, b: u32, c: u32) -> Self {
-        Self { pop, b, c }
+// The instructions in each block of the program, with `?` for the numbers that vary from block to
+// block.
+const BLOCK: [&str; 18] = [
+    "inp w", "mul x 0", "add x z", "mod x 26", "div z ?", "add x ?", "eql x w", "eql x 0",
+    "mul y 0", "add y 25", "mul y x", "add y 1", "mul z y", "mul y 0", "add y w", "add y ?",
+    "mul y x", "add z y",
+];
+
+// Recognise the fourteen blocks of a MONAD program, and read what each one does.
+fn analyse(program: &Program) -> Result<[BlackBox; 14], ParseError> {
+    let instructions = &program.instructions;
+    let mut numbers = vec![];
+    for index in 0..14 * BLOCK.len() {
+        let expected = BLOCK[index % BLOCK.len()];
+        let shown = expected.replace('?', "<number>");
+        let found = match instructions.get(index) {
+            Some(instruction) => instruction.to_string(),
+            None => {
+                let message = format!("expected `{}`, found end of input", shown);
+                return Err(ParseError::new(message).line_offset(index));
+            }
+        };
+        let matches = match expected.strip_suffix('?') {
+            Some(prefix) => match found.strip_prefix(prefix).map(str::parse::<i64>) {
+                Some(Ok(number)) => {
+                    numbers.push(number);
+                    true
+                }
+                _ => false,
+            },
+            None => found == expected,
+        };
+        if !matches {
+            let message = format!("expected `{}`, found `{}`", shown, found);
+            return Err(ParseError::new(message).line_offset(index));
+        }
+    }
+    if let Some(extra) = instructions.get(14 * BLOCK.len()) {
+        let message = format!("expected end of input, found `{}`", extra);
+        return Err(ParseError::new(message).line_offset(14 * BLOCK.len()));
     }
 
-    // Simplified instructions, which we interpret as operating on a stack of digits (mod 26).
-    //
-    // Each box maybe pops and maybe pushes a digit.  Boxes that don't pop are always set up so
-    // that they are guaranteed to push.
-    fn run(&self, w: u32, mut z: u32) -> u32 {
-        let x = z % 26;
+    let boxes = numbers
+        .chunks(3)
+        .enumerate()
+        .map(|(block, numbers)| {
+            let pop = match numbers[0] {
+                1 => false,
+                26 => true,
+                divisor => {
+                    let message = format!(
+                        "expected `div z 1` or `div z 26`, found `div z {}`",
+                        divisor
+                    );
+                    return Err(ParseError::new(message).line_offset(block * BLOCK.len() + 4));
+                }
+            };
+            Ok(BlackBox {
+                pop,
+                b: numbers[1],
+                c: numbers[2],
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(boxes.try_into().unwrap())
+}
 
-        if self.pop {
-            z /= 26;
+/// A requirement that one digit of the model number is a fixed amount more than an earlier one.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Constraint {
+    /// The positions of the two digits, counting from zero at the most significant.
+    pub first: usize,
+    pub second: usize,
+    /// How much more the second digit is than the first.
+    pub difference: i64,
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "w[{}] = w[{}]", self.second, self.first)?;
+        match self.difference {
+            0 => Ok(()),
+            d if d > 0 => write!(f, " + {}", d),
+            d => write!(f, " - {}", -d),
         }
+    }
+}
 
-        if !self.pop || x != w + self.b {
-            z *= 26;
-            z += w + self.c;
+// We interpret the boxes as operating on a stack of digits (mod 26), held in `z`.
+//
+// A box that doesn't pop always pushes its digit `w_i` plus `c_i`. A box that pops takes off
+// the top of the stack, and pushes `w_j + c_j` back on unless `w_j = top + b_j`. Since `z` must end
+// up zero, every push must be undone by a pop that matches; that pairs the boxes up into
+// constraints `w_j = w_i + c_i + b_j`.
+fn constraints(boxes: &[BlackBox]) -> Result<Vec<Constraint>, ParseError> {
+    let mut stack = vec![];
+    let mut constraints = vec![];
+    for (index, black_box) in boxes.iter().enumerate() {
+        let line = index * BLOCK.len();
+        if black_box.pop {
+            let (first, c) = stack.pop().ok_or_else(|| {
+                ParseError::new("expected a digit to pop, found an empty stack")
+                    .line_offset(line + 4)
+            })?;
+            constraints.push(Constraint {
+                first,
+                second: index,
+                difference: c + black_box.b,
+            });
+        } else {
+            // Anything from `b` to `b + 25` must not match a digit, else this might not push.
+            if (-24..=9).contains(&black_box.b) {
+                let message = format!(
+                    "expected `add x` to be at least 10 when not popping, found {}",
+                    black_box.b
+                );
+                return Err(ParseError::new(message).line_offset(line + 5));
+            }
+            // Pushed values must fit in one place on the stack, and not be zero.
+            if !(0..=16).contains(&black_box.c) {
+                let message = format!("expected `add y` from 0 to 16, found {}", black_box.c);
+                return Err(ParseError::new(message).line_offset(line + 15));
+            }
+            stack.push((index, black_box.c));
         }
+    }
+    if !stack.is_empty() {
+        let message = format!(
+            "expected every digit pushed to be popped, found {} left over",
+            stack.len()
+        );
+        return Err(ParseError::new(message));
+    }
+    constraints.sort_by_key(|constraint| constraint.first);
+    Ok(constraints)
+}
+
+/// The model number checking program, along with what we know about how it works.
+#[derive(Debug)]
+pub struct Monad {
+    program: Program,
+    // The digit constraints, or why the program can't be understood as blocks on a stack.
+    constraints: Result<Vec<Constraint>, ParseError>,
+}
+
+impl Monad {
+    /// What the program requires of the digits of a model number, if it is made of the usual
+    /// blocks.
+    pub fn constraints(&self) -> Result<&[Constraint], &ParseError> {
+        self.constraints.as_deref()
+    }
+
+    // The largest or smallest valid model number, if there is one. Programs that don't follow the
+    // usual pattern are searched digit by digit.
+    fn extreme(&self, largest: bool) -> Option<u64> {
+        let digits = match &self.constraints {
+            Ok(constraints) => Self::meet(constraints, largest)?.to_vec(),
+            Err(_) => self.program.search_digits(largest)?,
+        };
+        Some(
+            digits
+                .iter()
+                .fold(0, |number, &digit| 10 * number + digit as u64),
+        )
+    }
+
+    // The largest or smallest digits meeting every constraint, if there are any.
+    fn meet(constraints: &[Constraint], largest: bool) -> Option<[i64; 14]> {
+        let mut digits = [0; 14];
+        for constraint in constraints {
+            let difference = constraint.difference;
+            if difference.abs() > 8 {
+                return None;
+            }
+            // Make whichever digit is bigger as big as it can be, or the smaller one as small.
+            let first = match (largest, difference > 0) {
+                (true, true) => 9 - difference,
+                (true, false) => 9,
+                (false, true) => 1,
+                (false, false) => 1 - difference,
+            };
+            digits[constraint.first] = first;
+            digits[constraint.second] = first + difference;
+        }
+        Some(digits)
+    }
+
+    /// The largest valid model number.
+    pub fn largest(&self) -> Option<u64> {
+        self.extreme(true)
+    }
+
+    /// The smallest valid model number.
+    pub fn smallest(&self) -> Option<u64> {
+        self.extreme(false)
+    }
+
+    /// How many valid model numbers there are, if the program is made of the usual blocks.
+    pub fn count(&self) -> Option<u64> {
+        let constraints = self.constraints.as_ref().ok()?;
+        let count = constraints
+            .iter()
+            .map(|constraint| 9_u64.saturating_sub(constraint.difference.unsigned_abs()))
+            .product();
+        Some(count)
+    }
+
+    /// Whether the program accepts a model number, by leaving zero in `z`.
+    ///
+    /// Model numbers containing zeros are never valid.
+    pub fn accepts(&self, model_number: u64) -> Result<bool, Fault> {
+        let digits: Vec<i64> = model_number
+            .to_string()
+            .bytes()
+            .map(|digit| i64::from(digit - b'0'))
+            .collect();
+        if digits.contains(&0) {
+            return Ok(false);
+        }
+        let registers = self.program.run(digits)?;
+        Ok(registers.get(Register::Z) == 0)
+    }
+
+    // Check a model number found by analysis against the program itself.
+    fn verified(&self, model_number: Option<u64>) -> Result<u64, NoModelNumber> {
+        let model_number = model_number.ok_or(NoModelNumber::NotFound)?;
+        match self.accepts(model_number) {
+            Ok(true) => Ok(model_number),
+            Ok(false) => Err(NoModelNumber::Rejected(model_number)),
+            Err(fault) => Err(NoModelNumber::Fault(model_number, fault)),
+        }
+    }
+}
 
-        z
+/// Why there is no answer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NoModelNumber {
+    NotFound,
+    /// The program did not accept the model number that the analysis came up with.
+    Rejected(u64),
+    /// The program failed while checking this model number.
+    Fault(u64, Fault),
+}
+
+impl fmt::Display for NoModelNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "no model number is valid"),
+            Self::Rejected(number) => write!(f, "the program rejects {}", number),
+            Self::Fault(number, fault) => write!(f, "checking {} failed at {}", number, fault),
+        }
     }
 }
 
-fn solve(boxes: &[BlackBox], min: bool, ws: u64, z: u32) -> Option<u64> {
-    if boxes.is_empty() {
-        return Some(ws);
-    }
-
-    // Count either up or down.
-    let iter = if min {
-        Either::Left(1..=9)
-    } else {
-        Either::Right((1..=9).rev())
-    };
-
-    // Since we have seven boxes that will definitely push to the stack, we'd better arrange that
-    // the seven boxes that pop from it do nothing else.
-    let next_box = &boxes[0];
-    for w in iter {
-        let new_z = next_box.run(w, z);
-        if next_box.pop && new_z != z / 26 {
-            continue;
+impl std::error::Error for NoModelNumber {}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+
+    type Input = Monad;
+    type PartOne = Result<u64, NoModelNumber>;
+    type PartTwo = Result<u64, NoModelNumber>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let program: Program = input.parse()?;
+
+        // Model numbers must have at least one digit, and fit in a `u64`.
+        let inputs = program
+            .instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, Instruction::Inp(_)))
+            .count();
+        if inputs == 0 {
+            return Err(ParseError::new("expected an `inp` instruction, found none"));
         }
-        let new_ws = 10 * ws + w as u64;
-        if let Some(solution) = solve(&boxes[1..], min, new_ws, new_z) {
-            return Some(solution);
+        if inputs > 19 {
+            let message = format!("expected at most 19 `inp` instructions, found {}", inputs);
+            return Err(ParseError::new(message));
         }
+
+        let constraints = analyse(&program).and_then(|boxes| constraints(&boxes));
+        Ok(Monad {
+            program,
+            constraints,
+        })
     }
 
-    None
+    fn part_one(monad: &Self::Input) -> Self::PartOne {
+        monad.verified(monad.largest())
+    }
+
+    fn part_two(monad: &Self::Input) -> Self::PartTwo {
+        monad.verified(monad.smallest())
+    }
 }
 
-pub(crate) fn day24() {
-    let boxes = [
-        BlackBox::new(false, 11, 5),
-        BlackBox::new(false, 13, 5),
-        BlackBox::new(false, 12, 1),
-        BlackBox::new(false, 15, 15),
-        BlackBox::new(false, 10, 2),
-        BlackBox::new(true, 1, 2),
-        BlackBox::new(false, 14, 5),
-        BlackBox::new(true, 8, 8),
-        BlackBox::new(true, 7, 14),
-        BlackBox::new(true, 8, 12),
-        BlackBox::new(false, 11, 7),
-        BlackBox::new(true, 2, 14),
-        BlackBox::new(true, 2, 13),
-        BlackBox::new(true, 13, 6),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A MONAD program made of blocks with the given `div z`, `add x` and `add y` operands.
+    fn program(blocks: &[(i64, i64, i64)]) -> String {
+        blocks
+            .iter()
+            .map(|(div, check, offset)| {
+                format!(
+                    "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}\nadd x {}\neql x w\neql x 0\n\
+                     mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\n\
+                     add y {}\nmul y x\nadd z y\n",
+                    div, check, offset
+                )
+            })
+            .collect()
+    }
+
+    const BLOCKS: [(i64, i64, i64); 14] = [
+        (1, 11, 5),
+        (1, 13, 5),
+        (1, 12, 1),
+        (1, 15, 15),
+        (1, 10, 2),
+        (26, -1, 2),
+        (1, 14, 5),
+        (26, -8, 8),
+        (26, -7, 14),
+        (26, -8, 12),
+        (1, 11, 7),
+        (26, -2, 14),
+        (26, -2, 13),
+        (26, -13, 6),
     ];
 
-    let part_one = solve(&boxes, false, 0, 0).unwrap();
-    println!("Part one answer is {}", part_one);
+    // Why a program can't be understood as blocks on a stack.
+    fn analysis_error(text: &str) -> ParseError {
+        let monad = Day24::parse(text).unwrap();
+        monad.constraints().unwrap_err().clone()
+    }
+
+    #[test]
+    fn answers() {
+        let monad = Day24::parse(&program(&BLOCKS)).unwrap();
+        assert_eq!(Day24::part_one(&monad), Ok(96918996924991));
+        assert_eq!(Day24::part_two(&monad), Ok(91811241911641));
+    }
 
-    let part_two = solve(&boxes, true, 0, 0).unwrap();
-    println!("Part two answer is {}", part_two);
+    #[test]
+    fn accepts() {
+        let monad = Day24::parse(&program(&BLOCKS)).unwrap();
+        assert_eq!(monad.accepts(96918996924991), Ok(true));
+        assert_eq!(monad.accepts(96918996924992), Ok(false));
+        assert_eq!(monad.accepts(96918996924901), Ok(false));
+    }
+
+    #[test]
+    fn wrong_shape() {
+        let text = program(&BLOCKS).replacen("mod x 26", "mod x 25", 2);
+        let error = analysis_error(&text);
+        assert_eq!(error.line, Some(4));
+        assert_eq!(error.message, "expected `mod x 26`, found `mod x 25`");
+
+        let text = program(&BLOCKS[..13]);
+        let error = analysis_error(&text);
+        assert_eq!(error.line, Some(235));
+        assert_eq!(error.message, "expected `inp w`, found end of input");
+
+        let text = program(&BLOCKS).replacen("div z 26", "div z 13", 1);
+        let error = analysis_error(&text);
+        assert_eq!(error.line, Some(95));
+
+        let text = program(&BLOCKS).replacen("add y 5", "add y z", 1);
+        let error = analysis_error(&text);
+        assert_eq!(error.message, "expected `add y <number>`, found `add y z`");
+    }
+
+    #[test]
+    fn constraints() {
+        let monad = Day24::parse(&program(&BLOCKS)).unwrap();
+        let constraints: Vec<String> = monad
+            .constraints()
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let expected = [
+            "w[13] = w[0] - 8",
+            "w[12] = w[1] + 3",
+            "w[9] = w[2] - 7",
+            "w[8] = w[3] + 8",
+            "w[5] = w[4] + 1",
+            "w[7] = w[6] - 3",
+            "w[11] = w[10] + 5",
+        ];
+        assert_eq!(constraints, expected);
+        assert_eq!(monad.count(), Some(2304));
+    }
+
+    #[test]
+    fn no_stack() {
+        let mut blocks = BLOCKS;
+        blocks[4].1 = 5;
+        let error = analysis_error(&program(&blocks));
+        assert_eq!(error.line, Some(78));
+
+        blocks = BLOCKS;
+        blocks.swap(0, 13);
+        let error = analysis_error(&program(&blocks));
+        assert_eq!(error.line, Some(5));
+
+        // A difference of nine between paired digits can't be met.
+        blocks = BLOCKS;
+        blocks[13].1 = -14;
+        let monad = Day24::parse(&program(&blocks)).unwrap();
+        assert_eq!(monad.count(), Some(0));
+        assert_eq!(Day24::part_one(&monad), Err(NoModelNumber::NotFound));
+    }
+
+    #[test]
+    fn any_program() {
+        // An extra instruction at the end means that the program no longer fits the pattern.
+        let text = program(&BLOCKS) + "mul x 0\n";
+        let monad = Day24::parse(&text).unwrap();
+        assert!(monad.constraints().is_err());
+        assert_eq!(monad.count(), None);
+        // Searching for the smallest model number takes longer, so only look for the largest.
+        assert_eq!(Day24::part_one(&monad), Ok(96918996924991));
+
+        // Three digits, where the last must be the sum of the first two.
+        let monad = Day24::parse("inp x\ninp y\nadd x y\ninp z\nmul z -1\nadd z x\n").unwrap();
+        assert_eq!(Day24::part_one(&monad), Ok(819));
+        assert_eq!(Day24::part_two(&monad), Ok(112));
+
+        let error = Day24::parse(&"inp w\n".repeat(20)).unwrap_err();
+        assert_eq!(
+            error.message,
+            "expected at most 19 `inp` instructions, found 20"
+        );
+
+        let error = Day24::parse(
+            "add z 0
+",
+        )
+        .unwrap_err();
+        assert_eq!(error.message, "expected an `inp` instruction, found none");
+    }
 }
@@ -1,100 +1,115 @@
+use crate::grid::{Grid, Point};
+use crate::{ParseError, Solution};
 use std::str::FromStr;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 enum SeaCucumber {
     Eastbound,
     Southbound,
 }
 
 impl TryFrom<char> for SeaCucumber {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
         let sea_cucumber = match c {
             '>' => SeaCucumber::Eastbound,
             'v' => SeaCucumber::Southbound,
-            _ => return Err(()),
+            _ => {
+                let message = format!("expected `>`, `v` or `.`, found `{}`", c);
+                return Err(ParseError::new(message));
+            }
         };
         Ok(sea_cucumber)
     }
 }
 
-struct SeaBed {
-    grid: Vec<Vec<Option<SeaCucumber>>>,
-    max_x: usize,
-    max_y: usize,
+#[derive(Clone)]
+pub struct SeaBed {
+    grid: Grid<Option<SeaCucumber>>,
 }
 
 impl FromStr for SeaBed {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut grid = vec![];
-        for line in s.lines() {
-            let row: Vec<Option<SeaCucumber>> = line.chars().map(|c| c.try_into().ok()).collect();
-            grid.push(row);
-        }
-
-        let sea_bed = Self::new(grid);
+        let grid = Grid::parse(s, "a map of the sea bed", |c| match c {
+            '.' => Ok(None),
+            _ => c.try_into().map(Some),
+        })?;
+        let sea_bed = Self { grid };
         Ok(sea_bed)
     }
 }
 
 impl SeaBed {
-    fn new(grid: Vec<Vec<Option<SeaCucumber>>>) -> Self {
-        let max_y = grid.len();
-        let max_x = grid[0].len();
-        Self { grid, max_x, max_y }
-    }
-
     fn step(&mut self) -> bool {
-        self.step_east() | self.step_south()
+        self.step_herd(SeaCucumber::Eastbound, (1, 0))
+            | self.step_herd(SeaCucumber::Southbound, (0, 1))
     }
 
-    fn step_east(&mut self) -> bool {
-        let mut moved = false;
-        for row in &mut self.grid {
-            let moves: Vec<usize> = (0..self.max_x)
-                .filter(|&x| {
-                    row[x] == Some(SeaCucumber::Eastbound) && row[(x + 1) % self.max_x].is_none()
-                })
-                .collect();
-
-            for &m in &moves {
-                row[(m + 1) % self.max_x] = row[m].take();
-            }
-            moved |= !moves.is_empty();
+    // Move every sea cucumber in the herd that has space to do so, returning whether any moved.
+    fn step_herd(&mut self, herd: SeaCucumber, direction: (isize, isize)) -> bool {
+        let moves: Vec<(Point, Point)> = self
+            .grid
+            .iter()
+            .filter(|(_, cell)| cell.as_ref() == Some(&herd))
+            .map(|(point, _)| (point, self.grid.wrapping_offset(point, direction)))
+            .filter(|&(_, target)| self.grid[target].is_none())
+            .collect();
+        for &(from, to) in &moves {
+            self.grid[to] = self.grid[from].take();
         }
+        !moves.is_empty()
+    }
+}
 
-        moved
+const EXAMPLE: &str = "\
+v...>>.vv>
+.vv>>.vv..
+>>.>v>...v
+>>v>>.>.v.
+v>v.vv.v..
+>.>>..v...
+.vv..>.>v.
+v.v..>>v.v
+....v..v.>
+";
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    const EXAMPLE: Option<&'static str> = Some(EXAMPLE);
+
+    type Input = SeaBed;
+    type PartOne = usize;
+    type PartTwo = ();
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input.parse()
     }
 
-    fn step_south(&mut self) -> bool {
-        let mut moved = false;
-        for x in 0..self.max_x {
-            let moves: Vec<usize> = (0..self.max_y)
-                .filter(|&y| {
-                    self.grid[y][x] == Some(SeaCucumber::Southbound)
-                        && self.grid[(y + 1) % self.max_y][x].is_none()
-                })
-                .collect();
-
-            for &m in &moves {
-                self.grid[(m + 1) % self.max_y][x] = self.grid[m][x].take();
-            }
-            moved |= !moves.is_empty();
+    fn part_one(sea_bed: &Self::Input) -> Self::PartOne {
+        let mut sea_bed = sea_bed.clone();
+        let mut steps = 0;
+        while sea_bed.step() {
+            steps += 1;
         }
-
-        moved
+        steps + 1
     }
+
+    // There is no second puzzle on the last day.
+    fn part_two(_sea_bed: &Self::Input) -> Self::PartTwo {}
 }
 
-pub(crate) fn day25() {
-    let input = std::fs::read_to_string("data/day25.txt").unwrap();
-    let mut sea_bed: SeaBed = input.parse().unwrap();
-    let mut steps = 0;
-    while sea_bed.step() {
-        steps += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = Day25::parse(EXAMPLE).unwrap();
+        assert_eq!(Day25::part_one(&input), 58);
     }
-    println!("Part one answer is {}", steps + 1);
 }
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A failure to parse puzzle input, with as much position information as is known.
+///
+/// Lines and columns are counted from one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub day: Option<u8>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            day: None,
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn in_day(mut self, day: u8) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    // The text that failed to parse started `offset` lines into the text we're reporting on.
+    pub fn line_offset(mut self, offset: usize) -> Self {
+        self.line = Some(offset + self.line.unwrap_or(1));
+        self
+    }
+
+    // The text that failed to parse started `offset` bytes into the line we're reporting on.
+    pub fn column_offset(mut self, offset: usize) -> Self {
+        self.column = Some(offset + self.column.unwrap_or(1));
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}, ", day)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, "column {}, ", column)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// The byte offset of `part` in `whole`, where `part` must be a slice of `whole`.
+pub(crate) fn offset(whole: &str, part: &str) -> usize {
+    part.as_ptr() as usize - whole.as_ptr() as usize
+}
+
+// Parse `word`, which is a slice of `line`, describing it as `what` if it doesn't parse.
+pub(crate) fn parse_word<T: FromStr>(line: &str, word: &str, what: &str) -> Result<T, ParseError> {
+    word.parse().map_err(|_| {
+        ParseError::new(format!("expected {}, found `{}`", what, word))
+            .column_offset(offset(line, word))
+    })
+}
+
+// The next word from an iterator over slices of `line`, parsed as `what`.
+pub(crate) fn next_word<'a, T: FromStr>(
+    line: &str,
+    words: &mut impl Iterator<Item = &'a str>,
+    what: &str,
+) -> Result<T, ParseError> {
+    let word = words.next().ok_or_else(|| {
+        ParseError::new(format!("expected {}, found end of line", what)).column_offset(line.len())
+    })?;
+    parse_word(line, word, what)
+}
+
+// Consume the next word from an iterator over slices of `line`, which must be `expected`.
+pub(crate) fn expect_word<'a>(
+    line: &str,
+    words: &mut impl Iterator<Item = &'a str>,
+    expected: &str,
+) -> Result<(), ParseError> {
+    match words.next() {
+        Some(word) if word == expected => Ok(()),
+        Some(word) => Err(
+            ParseError::new(format!("expected `{}`, found `{}`", expected, word))
+                .column_offset(offset(line, word)),
+        ),
+        None => Err(
+            ParseError::new(format!("expected `{}`, found end of line", expected))
+                .column_offset(line.len()),
+        ),
+    }
+}
+
+// Parse each line of the input in turn, adding line numbers to any error.
+pub(crate) fn parse_lines<T>(
+    input: &str,
+    parser: impl Fn(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| parser(line).map_err(|error| error.line_offset(index)))
+        .collect()
+}
+
+// Parse a single decimal digit.
+pub(crate) fn parse_digit(c: char) -> Result<u32, ParseError> {
+    c.to_digit(10)
+        .ok_or_else(|| ParseError::new(format!("expected a digit, found `{}`", c)))
+}
@@ -0,0 +1,281 @@
+use crate::ParseError;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A position in a grid, as `(x, y)` with `y` counting down from the top row.
+pub type Point = (usize, usize);
+
+/// Which of the surrounding cells count as neighbours.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Neighbourhood {
+    /// Up, down, left and right.
+    Orthogonal,
+    /// The four corners.
+    Diagonal,
+    /// All eight surrounding cells.
+    All,
+}
+
+impl Neighbourhood {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Orthogonal => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Self::Diagonal => &[(-1, -1), (1, -1), (-1, 1), (1, 1)],
+            Self::All => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// A rectangular grid of cells, stored densely in row-major order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid whose cells are computed from their positions.
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut cell)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parse a character map, one row per line, converting each character with `cell`.
+    ///
+    /// Rows must all be the same length, and there must be at least one of them. `what` describes
+    /// the grid in error messages.
+    pub fn parse(
+        s: &str,
+        what: &str,
+        cell: impl Fn(char) -> Result<T, ParseError>,
+    ) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for (y, line) in s.lines().enumerate() {
+            let row_start = cells.len();
+            for (x, c) in line.char_indices() {
+                let value = cell(c).map_err(|error| error.column_offset(x).line_offset(y))?;
+                cells.push(value);
+            }
+            let row_width = cells.len() - row_start;
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => {
+                    let message = format!("expected {} cells, found {}", width, row_width);
+                    return Err(ParseError::new(message).line_offset(y));
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Self {
+                width,
+                height,
+                cells,
+            }),
+            _ => Err(ParseError::new(format!(
+                "expected {}, found empty input",
+                what
+            ))),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.contains(point).then(|| &self[point])
+    }
+
+    /// Every position in the grid, in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell in the grid along with its position, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Point, &mut T)> {
+        self.points().zip(self.cells.iter_mut())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// The position `delta` away from `point`, if that is still in the grid.
+    pub fn offset(&self, (x, y): Point, (dx, dy): (isize, isize)) -> Option<Point> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        self.contains((x, y)).then_some((x, y))
+    }
+
+    /// The position `delta` away from `point`, wrapping around at the edges of the grid.
+    pub fn wrapping_offset(&self, (x, y): Point, (dx, dy): (isize, isize)) -> Point {
+        let wrap = |coordinate: usize, delta: isize, size: usize| {
+            (coordinate as isize + delta).rem_euclid(size as isize) as usize
+        };
+        (wrap(x, dx, self.width), wrap(y, dy, self.height))
+    }
+
+    /// The neighbours of `point` that are in the grid.
+    pub fn neighbours(
+        &self,
+        point: Point,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = Point> + '_ {
+        neighbourhood
+            .offsets()
+            .iter()
+            .filter_map(move |&delta| self.offset(point, delta))
+    }
+
+    /// The neighbours of `point`, treating the grid as wrapping around at its edges.
+    pub fn wrapping_neighbours(
+        &self,
+        point: Point,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = Point> + '_ {
+        neighbourhood
+            .offsets()
+            .iter()
+            .map(move |&delta| self.wrapping_offset(point, delta))
+    }
+
+    fn index_of(&self, (x, y): Point) -> usize {
+        assert!(
+            self.contains((x, y)),
+            "({}, {}) is outside a {}x{} grid",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        y * self.width + x
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        &self.cells[self.index_of(point)]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        let index = self.index_of(point);
+        &mut self.cells[index]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height {
+            for cell in &self.cells[y * self.width..(y + 1) * self.width] {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::parse_digit;
+
+    fn digits(s: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(s, "a grid", parse_digit)
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = digits("123\n456\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.to_string(), "123\n456\n");
+    }
+
+    #[test]
+    fn empty_rows() {
+        let grid = Grid::from_fn(0, 2, |_| 0);
+        assert!(grid.is_empty());
+        assert_eq!(grid.to_string(), "\n\n");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = digits("123\n4x6\n").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(2)));
+        let error = digits("123\n45\n").unwrap_err();
+        assert_eq!(error.line, Some(2));
+        assert!(digits("").is_err());
+    }
+
+    #[test]
+    fn neighbourhoods() {
+        let grid = digits("123\n456\n789\n").unwrap();
+        let around = |point, neighbourhood| {
+            let mut values: Vec<u32> = grid
+                .neighbours(point, neighbourhood)
+                .map(|neighbour| grid[neighbour])
+                .collect();
+            values.sort_unstable();
+            values
+        };
+        assert_eq!(around((0, 0), Neighbourhood::Orthogonal), [2, 4]);
+        assert_eq!(around((1, 1), Neighbourhood::Diagonal), [1, 3, 7, 9]);
+        assert_eq!(around((2, 1), Neighbourhood::All), [2, 3, 5, 8, 9]);
+
+        let mut wrapped: Vec<u32> = grid
+            .wrapping_neighbours((0, 0), Neighbourhood::Orthogonal)
+            .map(|neighbour| grid[neighbour])
+            .collect();
+        wrapped.sort_unstable();
+        assert_eq!(wrapped, [2, 3, 4, 7]);
+    }
+}
@@ -0,0 +1,94 @@
+use advent_of_code_2021::{Answer, Report};
+use std::fmt::Write;
+use std::time::Duration;
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Answers are always strings, so that a field's type doesn't depend on the answer: some are too
+// large to be JSON numbers without losing precision.
+fn answer(answer: Option<&Answer>) -> String {
+    match answer {
+        Some(Answer::Number(value)) => escape(&value.to_string()),
+        Some(Answer::Text(text)) => escape(text),
+        Some(Answer::Nothing | Answer::Unsolvable(_)) | None => "null".to_owned(),
+    }
+}
+
+fn nanos(duration: Option<Duration>) -> String {
+    duration.map_or_else(|| "null".to_owned(), |d| d.as_nanos().to_string())
+}
+
+// A single-line JSON object describing the outcome of running a day.
+//
+// Each part's answer is a string, or null if the part has no answer. Timings are whole
+// nanoseconds, and the error is a string or null.
+pub(crate) fn report(day: u8, result: &Result<Report, String>) -> String {
+    let report = result.as_ref().ok();
+    // An unsolvable part has no answer, and is reported as an error instead.
+    let unsolvable = report.and_then(|r| {
+        [&r.answers.part_one, &r.answers.part_two]
+            .into_iter()
+            .find(|answer| matches!(answer, Answer::Unsolvable(_)))
+            .map(Answer::to_string)
+    });
+    let error = result
+        .as_ref()
+        .err()
+        .cloned()
+        .or(unsolvable)
+        .map_or_else(|| "null".to_owned(), |error| escape(&error));
+    format!(
+        concat!(
+            "{{\"day\":{},\"part_one\":{},\"part_two\":{},",
+            "\"parse_ns\":{},\"part_one_ns\":{},\"part_two_ns\":{},\"error\":{}}}"
+        ),
+        day,
+        answer(report.map(|r| &r.answers.part_one)),
+        answer(report.map(|r| &r.answers.part_two)),
+        nanos(report.map(|r| r.parse_time)),
+        nanos(report.map(|r| r.part_one_time)),
+        nanos(report.map(|r| r.part_two_time)),
+        error,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code_2021::Answers;
+
+    #[test]
+    fn answers_are_strings() {
+        let answers = Answers {
+            part_one: Answer::Number(739785),
+            part_two: Answer::from(u128::MAX),
+        };
+        let outcome = Report {
+            answers,
+            parse_time: Duration::from_nanos(1),
+            part_one_time: Duration::from_nanos(2),
+            part_two_time: Duration::from_nanos(3),
+        };
+        let expected = concat!(
+            r#"{"day":21,"part_one":"739785","part_two":"340282366920938463463374607431768211455","#,
+            r#""parse_ns":1,"part_one_ns":2,"part_two_ns":3,"error":null}"#
+        );
+        assert_eq!(report(21, &Ok(outcome)), expected);
+    }
+}
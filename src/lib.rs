@@ -0,0 +1,179 @@
+use std::time::{Duration, Instant};
+
+pub mod alu;
+mod answer;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+mod error;
+pub mod grid;
+pub mod search;
+
+pub use answer::{Answer, Answers};
+pub use error::ParseError;
+
+/// A solver for one day's puzzle.
+///
+/// The puzzle input is parsed once, and then both parts are solved from the parsed form.
+pub trait Solution {
+    const DAY: u8;
+
+    /// The worked example from the puzzle description, if there is one.
+    const EXAMPLE: Option<&'static str> = None;
+
+    type Input;
+    type PartOne: Into<Answer>;
+    type PartTwo: Into<Answer>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part_one(input: &Self::Input) -> Self::PartOne;
+    fn part_two(input: &Self::Input) -> Self::PartTwo;
+}
+
+/// The answers from running a solution, and how long each stage took.
+pub struct Report {
+    pub answers: Answers,
+    pub parse_time: Duration,
+    pub part_one_time: Duration,
+    pub part_two_time: Duration,
+}
+
+impl Report {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.part_one_time + self.part_two_time
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// One of the two parts of a puzzle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+/// Parse the input and solve the requested part, or both parts if none is given.
+///
+/// A part that is not run has no answer, and takes no time.
+pub fn run<S: Solution>(input: &str, part: Option<Part>) -> Result<Report, ParseError> {
+    let wanted = |this| part.is_none_or(|part| part == this);
+    let (parsed, parse_time) = timed(|| S::parse(input));
+    let parsed = parsed.map_err(|error| error.in_day(S::DAY))?;
+    let (part_one, part_one_time) = if wanted(Part::One) {
+        timed(|| S::part_one(&parsed).into())
+    } else {
+        (Answer::Nothing, Duration::ZERO)
+    };
+    let (part_two, part_two_time) = if wanted(Part::Two) {
+        timed(|| S::part_two(&parsed).into())
+    } else {
+        (Answer::Nothing, Duration::ZERO)
+    };
+    let answers = Answers { part_one, part_two };
+    let report = Report {
+        answers,
+        parse_time,
+        part_one_time,
+        part_two_time,
+    };
+    Ok(report)
+}
+
+/// A type-erased [`run`] for some solution.
+pub type Runner = fn(&str, Option<Part>) -> Result<Report, ParseError>;
+
+/// Everything the driver needs to know about one day's puzzle.
+pub struct Puzzle {
+    pub day: u8,
+    pub run: Runner,
+    pub example: Option<&'static str>,
+}
+
+const fn of<S: Solution>() -> Puzzle {
+    Puzzle {
+        day: S::DAY,
+        run: run::<S>,
+        example: S::EXAMPLE,
+    }
+}
+
+/// Every implemented puzzle, in order.
+pub const PUZZLES: [Puzzle; 25] = [
+    of::<day01::Day01>(),
+    of::<day02::Day02>(),
+    of::<day03::Day03>(),
+    of::<day04::Day04>(),
+    of::<day05::Day05>(),
+    of::<day06::Day06>(),
+    of::<day07::Day07>(),
+    of::<day08::Day08>(),
+    of::<day09::Day09>(),
+    of::<day10::Day10>(),
+    of::<day11::Day11>(),
+    of::<day12::Day12>(),
+    of::<day13::Day13>(),
+    of::<day14::Day14>(),
+    of::<day15::Day15>(),
+    of::<day16::Day16>(),
+    of::<day17::Day17>(),
+    of::<day18::Day18>(),
+    of::<day19::Day19>(),
+    of::<day20::Day20>(),
+    of::<day21::Day21>(),
+    of::<day22::Day22>(),
+    of::<day23::Day23>(),
+    of::<day24::Day24>(),
+    of::<day25::Day25>(),
+];
+
+/// The puzzle for a given day, if it is implemented.
+pub fn puzzle(day: u8) -> Option<&'static Puzzle> {
+    let index = usize::from(day).checked_sub(1)?;
+    PUZZLES.get(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puzzles_are_in_order() {
+        for (index, puzzle) in PUZZLES.iter().enumerate() {
+            assert_eq!(usize::from(puzzle.day), index + 1);
+        }
+    }
+
+    #[test]
+    fn run_single_part() {
+        let report = run::<day01::Day01>(day01::Day01::EXAMPLE.unwrap(), Some(Part::Two)).unwrap();
+        assert_eq!(report.answers.part_one, Answer::Nothing);
+        assert_eq!(report.answers.part_two, Answer::Number(5));
+    }
+}
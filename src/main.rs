@@ -1,63 +1,337 @@
+use advent_of_code_2021::day21::{self, Day21, Rules};
+use advent_of_code_2021::{Answer, Part, Puzzle, Report, Solution};
+use std::any::Any;
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+use verify::{Expected, Outcome, Tally};
+
+mod json;
+mod verify;
+
+const IMPLEMENTED: RangeInclusive<u8> = 1..=advent_of_code_2021::PUZZLES.len() as u8;
+
+// A single day, or a range such as `1..10` or `1..=10`.
+struct Days(RangeInclusive<u8>);
+
+impl FromStr for Days {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_day = |word: &str| {
+            word.parse::<u8>()
+                .map_err(|_| format!("Invalid day: {}", word))
+        };
+        let days = if let Some((first, last)) = s.split_once("..=") {
+            parse_day(first)?..=parse_day(last)?
+        } else if let Some((first, end)) = s.split_once("..") {
+            parse_day(first)?..=parse_day(end)?.saturating_sub(1)
+        } else {
+            let day = parse_day(s)?;
+            day..=day
+        };
+        if days.is_empty() {
+            return Err(format!("Empty range of days: {}", s));
+        }
+
+        for day in [days.start(), days.end()] {
+            if !IMPLEMENTED.contains(day) {
+                return Err(format!("Unimplemented day: {}", day));
+            }
+        }
+        Ok(Self(days))
+    }
+}
+
+enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unknown format: {}", s)),
+        }
+    }
+}
+
+// Which part to run, as given on the command line.
+struct PartArg(Part);
+
+impl FromStr for PartArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self(Part::One)),
+            "2" => Ok(Self(Part::Two)),
+            _ => Err(format!("Unknown part: {}", s)),
+        }
+    }
+}
 
 #[derive(StructOpt)]
+#[structopt(setting = AppSettings::SubcommandsNegateReqs)]
 struct Cli {
-    day: u8,
+    /// Day to run, or a range of days such as `1..=10`.
+    #[structopt(required_unless = "all")]
+    days: Option<Days>,
+
+    /// Run every implemented day.
+    #[structopt(long, conflicts_with = "days")]
+    all: bool,
+
+    /// Puzzle input file, or `-` to read from stdin. Defaults to `data/dayNN.txt`.
+    #[structopt(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Run on the worked example from the puzzle description instead of the puzzle input.
+    #[structopt(long, conflicts_with = "input")]
+    example: bool,
+
+    /// Run only this part, `1` or `2`, rather than both.
+    #[structopt(long, possible_values = &["1", "2"])]
+    part: Option<PartArg>,
+
+    /// Output format: `text`, or `json` for one object per day, with answers as strings.
+    #[structopt(long, default_value = "text", possible_values = &["text", "json"])]
+    format: Format,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Check answers against an expected-answers file.
+    Verify {
+        /// File of `day part value` lines.
+        #[structopt(long, default_value = "data/answers.txt", parse(from_os_str))]
+        answers: PathBuf,
+    },
+
+    /// Work out the exact odds of day 21's game with the Dirac die: win probabilities, game
+    /// lengths and expected scores.
+    Analyse {
+        /// Puzzle input file, or `-` to read from stdin. Defaults to `data/day21.txt`.
+        #[structopt(short, long, parse(from_os_str))]
+        input: Option<PathBuf>,
+
+        /// Analyse the worked example from the puzzle description instead of the puzzle input.
+        #[structopt(long, conflicts_with = "input")]
+        example: bool,
+    },
+}
+
+// Where to find the input for a day.
+#[derive(Clone, Copy)]
+enum Source<'a> {
+    Default,
+    File(&'a Path),
+    Example,
+}
+
+fn read_file(path: &Path) -> std::io::Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+fn read_input(puzzle: &Puzzle, source: Source) -> Result<String, String> {
+    let day = puzzle.day;
+    let path = match source {
+        Source::Default => PathBuf::from(format!("data/day{:02}.txt", day)),
+        Source::File(path) => path.to_path_buf(),
+        Source::Example => {
+            return puzzle
+                .example
+                .map(String::from)
+                .ok_or(format!("Day {} has no example", day))
+        }
+    };
+    read_file(&path).map_err(|error| format!("Failed to read input for day {}: {}", day, error))
+}
+
+fn run(day: u8, source: Source, part: Option<Part>) -> Result<Report, String> {
+    let puzzle = advent_of_code_2021::puzzle(day).ok_or(format!("Unimplemented day: {}", day))?;
+    let input = read_input(puzzle, source)?;
+    // Solvers should reject input they can't handle while parsing, but in case one doesn't, a
+    // panic is reported like any other failure rather than taking down the whole run.
+    std::panic::catch_unwind(|| (puzzle.run)(&input, part))
+        .map_err(|payload| format!("Day {} panicked: {}", day, panic_message(&*payload)))?
+        .map_err(|error| format!("Failed to parse input: {}", error))
+}
+
+// Panics carry their message as either a `&str` or a `String`.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown cause"
+    }
+}
+
+fn print_answer(part: &str, answer: &Answer) {
+    match answer {
+        // Multi-line answers are pictures, and should start on a line of their own.
+        Answer::Text(text) if text.contains('\n') => println!("Part {} answer is\n{}", part, text),
+        Answer::Nothing => {}
+        Answer::Unsolvable(reason) => println!("Part {} has no answer: {}", part, reason),
+        _ => println!("Part {} answer is {}", part, answer),
+    }
+}
+
+fn print_timings(reports: &[(u8, Report)]) {
+    println!(
+        "{:>3}  {:>12}  {:>12}  {:>12}  {:>12}",
+        "Day", "Parse", "Part one", "Part two", "Total"
+    );
+    for (day, report) in reports {
+        println!(
+            "{:>3}  {:>12.3?}  {:>12.3?}  {:>12.3?}  {:>12.3?}",
+            day,
+            report.parse_time,
+            report.part_one_time,
+            report.part_two_time,
+            report.total_time()
+        );
+    }
+    let total: Duration = reports.iter().map(|(_, report)| report.total_time()).sum();
+    println!("{:>3}  {:>54.3?}", "All", total);
+}
+
+fn verify(path: &Path) -> bool {
+    let expected = match Expected::read(path) {
+        Ok(expected) => expected,
+        Err(error) => {
+            eprintln!("{}", error);
+            return false;
+        }
+    };
+
+    let mut tally = Tally::default();
+    for (day, parts) in expected.days() {
+        let report = run(day, Source::Default, None);
+        for (part, value) in parts {
+            let outcome = match &report {
+                Ok(report) if *part == 1 => Outcome::check(value, &report.answers.part_one),
+                Ok(report) => Outcome::check(value, &report.answers.part_two),
+                Err(error) => Outcome::Fail(error.clone()),
+            };
+            println!("Day {:>2} part {}: {}", day, part, outcome);
+            tally.record(&outcome);
+        }
+    }
+
+    println!("{}", tally);
+    tally.all_passed()
+}
+
+fn analyse(source: Source) -> Result<String, String> {
+    let puzzle = advent_of_code_2021::puzzle(Day21::DAY).ok_or("Unimplemented day: 21")?;
+    let input = read_input(puzzle, source)?;
+    let players = Day21::parse(&input)
+        .map_err(|error| format!("Failed to parse input: {}", error.in_day(Day21::DAY)))?;
+    let analysis = day21::analyse(&players, &Rules::DIRAC).map_err(|error| error.to_string())?;
+    Ok(analysis.to_string())
 }
 
 fn main() {
+    // `run` reports panics itself, so the default report to stderr would only repeat it.
+    std::panic::set_hook(Box::new(|_| {}));
+
     let args = Cli::from_args();
-    match args.day {
-        1 => day01::day01(),
-        2 => day02::day02(),
-        3 => day03::day03(),
-        4 => day04::day04(),
-        5 => day05::day05(),
-        6 => day06::day06(),
-        7 => day07::day07(),
-        8 => day08::day08(),
-        9 => day09::day09(),
-        10 => day10::day10(),
-        11 => day11::day11(),
-        12 => day12::day12(),
-        13 => day13::day13(),
-        14 => day14::day14(),
-        15 => day15::day15(),
-        16 => day16::day16(),
-        17 => day17::day17(),
-        18 => day18::day18(),
-        19 => day19::day19(),
-        20 => day20::day20(),
-        21 => day21::day21(),
-        22 => day22::day22(),
-        23 => day23::day23(),
-        24 => day24::day24(),
-        25 => day25::day25(),
-        _ => println!("Unimplemented day: {}", args.day),
+    match &args.command {
+        Some(Command::Verify { answers }) => {
+            if !verify(answers) {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Analyse { input, example }) => {
+            let source = match input {
+                Some(path) => Source::File(path),
+                None if *example => Source::Example,
+                None => Source::Default,
+            };
+            match analyse(source) {
+                Ok(analysis) => print!("{}", analysis),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+
+    let days = match args.days {
+        Some(Days(days)) if !args.all => days,
+        _ => IMPLEMENTED,
+    };
+
+    let single_day = days.start() == days.end();
+    if args.input.is_some() && !single_day {
+        eprintln!("An input file can only be given when running a single day");
+        std::process::exit(1);
+    }
+
+    let source = match &args.input {
+        Some(path) => Source::File(path),
+        None if args.example => Source::Example,
+        None => Source::Default,
+    };
+
+    let part = args.part.map(|PartArg(part)| part);
+
+    if let Format::Json = args.format {
+        let mut failed = false;
+        for day in days {
+            let result = run(day, source, part);
+            println!("{}", json::report(day, &result));
+            failed |= result.is_err();
+        }
+        if failed {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut reports = vec![];
+    for day in days {
+        if !single_day {
+            println!("Day {}", day);
+        }
+        let report = match run(day, source, part) {
+            Ok(report) => report,
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        };
+        print_answer("one", &report.answers.part_one);
+        print_answer("two", &report.answers.part_two);
+        reports.push((day, report));
+    }
+
+    if !single_day {
+        println!();
+        print_timings(&reports);
     }
 }
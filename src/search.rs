@@ -0,0 +1,389 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// A shortest-path problem: where to start, how to move and what counts as done.
+pub trait Problem {
+    type State: Clone + Eq + Hash;
+
+    /// Costs must never be negative, and `Default` must give zero.
+    type Cost: Copy + Ord + Default + Add<Output = Self::Cost>;
+
+    fn start(&self) -> Self::State;
+
+    /// Add the states reachable in one move from `state` to `neighbours`, with the cost of each
+    /// move.
+    fn neighbours(&self, state: &Self::State, neighbours: &mut Vec<(Self::State, Self::Cost)>);
+
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    /// A lower bound on the cost of reaching a goal from `state`, used by [`astar`].
+    ///
+    /// Overestimating makes the search return paths that are not the cheapest.
+    fn heuristic(&self, _state: &Self::State) -> Self::Cost {
+        Self::Cost::default()
+    }
+
+    /// If states can be numbered densely from zero, how many numbers there are.
+    ///
+    /// The search then keeps track of states in tables indexed by [`Problem::state_index`],
+    /// which is much faster than hashing them.
+    fn state_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// The number of `state`, below [`Problem::state_count`]. Only called if that is `Some`.
+    fn state_index(&self, _state: &Self::State) -> usize {
+        unreachable!("states have no dense index")
+    }
+
+    /// The state numbered `index`, undoing [`Problem::state_index`].
+    fn indexed_state(&self, _index: usize) -> Self::State {
+        unreachable!("states have no dense index")
+    }
+}
+
+/// The cheapest route to a goal, including both the start and the goal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    pub states: Vec<S>,
+}
+
+/// Find a cheapest path with Dijkstra's algorithm, ignoring any heuristic.
+pub fn dijkstra<P: Problem>(problem: &P) -> Option<Path<P::State, P::Cost>> {
+    search(problem, |_| P::Cost::default())
+}
+
+/// Find a cheapest path with A*, guided by the problem's heuristic.
+pub fn astar<P: Problem>(problem: &P) -> Option<Path<P::State, P::Cost>> {
+    search(problem, |state| problem.heuristic(state))
+}
+
+// Every state reached so far, with the cheapest known route to it. Each state is stored as a
+// node, identified by a number.
+trait Store<S, C> {
+    // Record reaching `state` at `cost` from `parent`, unless there's already a route at least as
+    // cheap. Returns the state's node if this is an improvement.
+    fn improve(&mut self, state: S, cost: C, parent: Option<usize>) -> Option<usize>;
+
+    fn state(&self, node: usize) -> S;
+
+    fn cost(&self, node: usize) -> C;
+
+    // The start is its own parent.
+    fn parent(&self, node: usize) -> usize;
+}
+
+// Nodes numbered in the order they are reached, found by hashing their states.
+struct HashStore<S, C> {
+    nodes: HashMap<S, usize>,
+    states: Vec<S>,
+    costs: Vec<C>,
+    parents: Vec<usize>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy + Ord> Store<S, C> for HashStore<S, C> {
+    fn improve(&mut self, state: S, cost: C, parent: Option<usize>) -> Option<usize> {
+        match self.nodes.entry(state) {
+            Entry::Occupied(entry) => {
+                let node = *entry.get();
+                if cost >= self.costs[node] {
+                    return None;
+                }
+                self.costs[node] = cost;
+                self.parents[node] = parent.unwrap_or(node);
+                Some(node)
+            }
+            Entry::Vacant(entry) => {
+                let node = self.states.len();
+                self.states.push(entry.key().clone());
+                entry.insert(node);
+                self.costs.push(cost);
+                self.parents.push(parent.unwrap_or(node));
+                Some(node)
+            }
+        }
+    }
+
+    fn state(&self, node: usize) -> S {
+        self.states[node].clone()
+    }
+
+    fn cost(&self, node: usize) -> C {
+        self.costs[node]
+    }
+
+    fn parent(&self, node: usize) -> usize {
+        self.parents[node]
+    }
+}
+
+// Marks a node in a dense store that hasn't been reached.
+const UNSEEN: u32 = u32::MAX;
+
+// Nodes numbered by the problem's dense state index, so that there's no need for hashing, nor
+// for keeping the states themselves.
+struct DenseStore<'a, P: Problem> {
+    problem: &'a P,
+    costs: Vec<P::Cost>,
+    // Narrower than `usize`, since the table is touched for every state and halving it is
+    // measurably faster.
+    parents: Vec<u32>,
+}
+
+impl<P: Problem> Store<P::State, P::Cost> for DenseStore<'_, P> {
+    fn improve(&mut self, state: P::State, cost: P::Cost, parent: Option<usize>) -> Option<usize> {
+        let node = self.problem.state_index(&state);
+        if self.parents[node] != UNSEEN && cost >= self.costs[node] {
+            return None;
+        }
+        self.costs[node] = cost;
+        self.parents[node] = parent.unwrap_or(node) as u32;
+        Some(node)
+    }
+
+    fn state(&self, node: usize) -> P::State {
+        self.problem.indexed_state(node)
+    }
+
+    fn cost(&self, node: usize) -> P::Cost {
+        self.costs[node]
+    }
+
+    fn parent(&self, node: usize) -> usize {
+        self.parents[node] as usize
+    }
+}
+
+fn search<P: Problem>(
+    problem: &P,
+    heuristic: impl Fn(&P::State) -> P::Cost,
+) -> Option<Path<P::State, P::Cost>> {
+    match problem.state_count() {
+        Some(count) if count < UNSEEN as usize => {
+            let store = DenseStore {
+                problem,
+                costs: vec![P::Cost::default(); count],
+                parents: vec![UNSEEN; count],
+            };
+            search_in(problem, store, heuristic)
+        }
+        _ => {
+            let store = HashStore {
+                nodes: HashMap::new(),
+                states: vec![],
+                costs: vec![],
+                parents: vec![],
+            };
+            search_in(problem, store, heuristic)
+        }
+    }
+}
+
+fn search_in<P: Problem>(
+    problem: &P,
+    mut store: impl Store<P::State, P::Cost>,
+    heuristic: impl Fn(&P::State) -> P::Cost,
+) -> Option<Path<P::State, P::Cost>> {
+    let zero = P::Cost::default();
+    let start = problem.start();
+    let estimate = heuristic(&start);
+    let start = store.improve(start, zero, None).unwrap();
+    let mut queue = BinaryHeap::new();
+    queue.push(Queued {
+        estimate,
+        cost: zero,
+        node: start,
+    });
+
+    // The buffer of neighbours is reused, to save allocating one for every state.
+    let mut neighbours = vec![];
+    while let Some(Queued { cost, node, .. }) = queue.pop() {
+        // A cheaper route to this state has been found since this entry was queued.
+        if cost > store.cost(node) {
+            continue;
+        }
+
+        let state = store.state(node);
+        if problem.is_goal(&state) {
+            return Some(reconstruct(&store, node));
+        }
+
+        problem.neighbours(&state, &mut neighbours);
+        for (neighbour, step) in neighbours.drain(..) {
+            let new_cost = cost + step;
+            let estimate = new_cost + heuristic(&neighbour);
+            if let Some(next) = store.improve(neighbour, new_cost, Some(node)) {
+                queue.push(Queued {
+                    estimate,
+                    cost: new_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+// An entry in the search queue, which pops the lowest estimate first. Ties are left unbroken:
+// comparing anything more makes the queue noticeably slower.
+struct Queued<C> {
+    estimate: C,
+    cost: C,
+    node: usize,
+}
+
+impl<C: Ord> Ord for Queued<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+impl<C: Ord> PartialOrd for Queued<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Ord> PartialEq for Queued<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: Ord> Eq for Queued<C> {}
+
+fn reconstruct<S, C>(store: &impl Store<S, C>, goal: usize) -> Path<S, C> {
+    let mut nodes = vec![goal];
+    loop {
+        let node = *nodes.last().unwrap();
+        let parent = store.parent(node);
+        if parent == node {
+            break;
+        }
+        nodes.push(parent);
+    }
+    let states = nodes
+        .into_iter()
+        .rev()
+        .map(|node| store.state(node))
+        .collect();
+    Path {
+        cost: store.cost(goal),
+        states,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Walk along a line of numbered stops, where stepping forward costs one and jumping ahead
+    // five costs three.
+    struct Line {
+        length: u32,
+    }
+
+    impl Problem for Line {
+        type State = u32;
+        type Cost = u32;
+
+        fn start(&self) -> Self::State {
+            0
+        }
+
+        fn neighbours(
+            &self,
+            &state: &Self::State,
+            neighbours: &mut Vec<(Self::State, Self::Cost)>,
+        ) {
+            let moves = [(state + 1, 1), (state + 5, 3)];
+            neighbours.extend(moves.into_iter().filter(|&(next, _)| next <= self.length));
+        }
+
+        fn is_goal(&self, &state: &Self::State) -> bool {
+            state == self.length
+        }
+
+        fn heuristic(&self, &state: &Self::State) -> Self::Cost {
+            (self.length - state) * 3 / 5
+        }
+    }
+
+    #[test]
+    fn cheapest_path() {
+        let line = Line { length: 10 };
+        let expected = Path {
+            cost: 6,
+            states: vec![0, 5, 10],
+        };
+        assert_eq!(dijkstra(&line).map(|path| path.cost), Some(expected.cost));
+        assert_eq!(astar(&line), Some(expected));
+    }
+
+    #[test]
+    fn dense_states() {
+        // The same line, with its stops numbered for the search.
+        struct DenseLine(Line);
+
+        impl Problem for DenseLine {
+            type State = u32;
+            type Cost = u32;
+
+            fn start(&self) -> Self::State {
+                self.0.start()
+            }
+
+            fn neighbours(&self, state: &u32, neighbours: &mut Vec<(u32, u32)>) {
+                self.0.neighbours(state, neighbours);
+            }
+
+            fn is_goal(&self, state: &u32) -> bool {
+                self.0.is_goal(state)
+            }
+
+            fn state_count(&self) -> Option<usize> {
+                Some(self.0.length as usize + 1)
+            }
+
+            fn state_index(&self, &state: &u32) -> usize {
+                state as usize
+            }
+
+            fn indexed_state(&self, index: usize) -> u32 {
+                index as u32
+            }
+        }
+
+        let line = DenseLine(Line { length: 10 });
+        let expected = Path {
+            cost: 6,
+            states: vec![0, 5, 10],
+        };
+        assert_eq!(dijkstra(&line), Some(expected));
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        struct Stuck;
+
+        impl Problem for Stuck {
+            type State = ();
+            type Cost = u32;
+
+            fn start(&self) {}
+
+            fn neighbours(&self, _: &(), _: &mut Vec<((), u32)>) {}
+
+            fn is_goal(&self, _: &()) -> bool {
+                false
+            }
+        }
+
+        assert_eq!(dijkstra(&Stuck), None);
+    }
+}
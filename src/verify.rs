@@ -0,0 +1,128 @@
+use advent_of_code_2021::Answer;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+// Expected answers, keyed by day and then by part.
+pub(crate) struct Expected {
+    answers: BTreeMap<u8, Vec<(u8, String)>>,
+}
+
+impl Expected {
+    // Lines are `day part value`.  Blank lines and lines starting with `#` are ignored.
+    pub(crate) fn read(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+
+        let mut answers: BTreeMap<u8, Vec<(u8, String)>> = BTreeMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let bad_line = || {
+                format!(
+                    "{}:{}: expected `day part value`",
+                    path.display(),
+                    index + 1
+                )
+            };
+            // Any amount of whitespace may separate the fields, but the value is the whole of the
+            // rest of the line.
+            let (day, rest) = split_word(line).ok_or_else(bad_line)?;
+            let (part, value) = split_word(rest).ok_or_else(bad_line)?;
+            let day: u8 = day.parse().map_err(|_| bad_line())?;
+            let part: u8 = part
+                .parse()
+                .ok()
+                .filter(|part| matches!(part, 1 | 2))
+                .ok_or_else(bad_line)?;
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(bad_line());
+            }
+            answers
+                .entry(day)
+                .or_default()
+                .push((part, value.to_owned()));
+        }
+
+        Ok(Self { answers })
+    }
+
+    pub(crate) fn days(&self) -> impl Iterator<Item = (u8, &[(u8, String)])> {
+        self.answers
+            .iter()
+            .map(|(&day, parts)| (day, parts.as_slice()))
+    }
+}
+
+// The first word of `text`, and everything after the whitespace that ends it.
+fn split_word(text: &str) -> Option<(&str, &str)> {
+    text.trim_start().split_once(char::is_whitespace)
+}
+
+pub(crate) enum Outcome {
+    Pass,
+    Mismatch { expected: String, actual: Answer },
+    Fail(String),
+}
+
+impl Outcome {
+    pub(crate) fn check(expected: &str, actual: &Answer) -> Self {
+        if let Answer::Unsolvable(reason) = actual {
+            Self::Fail(format!("unsolvable: {}", reason))
+        } else if actual.to_string() == expected {
+            Self::Pass
+        } else {
+            Self::Mismatch {
+                expected: expected.to_owned(),
+                actual: actual.clone(),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pass => write!(f, "pass"),
+            Self::Mismatch { expected, actual } => {
+                write!(f, "MISMATCH (expected {}, got {})", expected, actual)
+            }
+            Self::Fail(reason) => write!(f, "FAIL ({})", reason),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Tally {
+    passed: usize,
+    mismatched: usize,
+    failed: usize,
+}
+
+impl Tally {
+    pub(crate) fn record(&mut self, outcome: &Outcome) {
+        match outcome {
+            Outcome::Pass => self.passed += 1,
+            Outcome::Mismatch { .. } => self.mismatched += 1,
+            Outcome::Fail(_) => self.failed += 1,
+        }
+    }
+
+    pub(crate) fn all_passed(&self) -> bool {
+        self.mismatched == 0 && self.failed == 0
+    }
+}
+
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} passed, {} mismatched, {} failed",
+            self.passed, self.mismatched, self.failed
+        )
+    }
+}